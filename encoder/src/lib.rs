@@ -46,13 +46,7 @@ impl Encoder {
     /// ```
     pub const fn new() -> Self {
         Self {
-            encode_table: [
-                b'A', b'B', b'C', b'D', b'E', b'F', b'G', b'H', b'I', b'J', b'K', b'L', b'M', b'N',
-                b'O', b'P', b'Q', b'R', b'S', b'T', b'U', b'V', b'W', b'X', b'Y', b'Z', b'a', b'b',
-                b'c', b'd', b'e', b'f', b'g', b'h', b'i', b'j', b'k', b'l', b'm', b'n', b'o', b'p',
-                b'q', b'r', b's', b't', b'u', b'v', b'w', b'x', b'y', b'z', b'0', b'1', b'2', b'3',
-                b'4', b'5', b'6', b'7', b'8', b'9', b'+', b'/',
-            ],
+            encode_table: *b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/",
         }
     }
 
@@ -677,6 +671,7 @@ irelia_encoder             ... bench: 8,069,776 ns/iter (+/- 95,354)
 irelia_encoder_ascii_check ... bench: 7,568,010 ns/iter (+/- 23,848)
 irelia_encoder_unchecked   ... bench: 7,190,625 ns/iter (+/- 58,626)
 */
+#[allow(clippy::needless_range_loop, clippy::unit_arg)]
 mod test {
     extern crate test;
 
@@ -28,11 +28,25 @@ fn main() {
         .subscribe(EventKind::JsonApiEvent, EventCounter(0))
         .unwrap();
 
-    while !ws_client.is_finished() {}
+    // Closures work as subscribers too, the argument type needs to be written out
+    let mut phases = 0;
+    let _phase = ws_client
+        .subscribe_guarded(
+            EventKind::JsonApiEventCallback("/lol-gameflow/v1/gameflow-phase".into()),
+            move |event: &Event| {
+                phases += 1;
+                println!("Phase change {phases}: {}", event.2.data);
+
+                ControlFlow::Continue(Flow::Continue)
+            },
+        )
+        .unwrap();
 
     thread::sleep(Duration::from_secs(15));
 
     ws_client.unsubscribe(EventKind::JsonApiEvent, id).unwrap();
 
+    ws_client.close().unwrap();
+
     println!("Done!");
 }
@@ -0,0 +1,29 @@
+use futures_util::StreamExt;
+use irelia::ws::stream::LCUWebSocketStream;
+use irelia::ws::types::EventKind;
+
+#[tokio::main]
+async fn main() {
+    let mut ws_client = LCUWebSocketStream::connect().await.unwrap();
+
+    ws_client.subscribe(&EventKind::JsonApiEvent).await.unwrap();
+
+    let mut count = 0;
+
+    while let Some(event) = ws_client.next().await {
+        println!("{:?}", event.unwrap());
+        count += 1;
+
+        if count == 10 {
+            break;
+        }
+    }
+
+    ws_client
+        .unsubscribe(&EventKind::JsonApiEvent)
+        .await
+        .unwrap();
+    ws_client.close().await.unwrap();
+
+    println!("Done!");
+}
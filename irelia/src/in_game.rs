@@ -2,13 +2,20 @@
 //!
 //! All types are all generated from the official JSON snippets
 
+pub mod derived;
+pub mod lifecycle;
+pub mod objectives;
+pub mod stats;
+pub mod stream;
 pub mod types;
 
-use hyper::body::Incoming;
-use hyper::Response;
+use std::borrow::Cow;
+
+use hyper::body::{Buf, Incoming};
+use hyper::{Response, StatusCode};
 use serde::de::DeserializeOwned;
 
-use crate::utils::requests::SerializeFormat;
+use crate::utils::requests::{api_error, Api, ErrorBody, SerializeFormat};
 use crate::{Error, RequestClient};
 
 use self::types::{
@@ -16,25 +23,33 @@ use self::types::{
     SummonerSpells, TeamID,
 };
 
-/// The only url the in game API can be used on
+/// The url the in game API is served on by the game
 pub const URL: &str = "127.0.0.1:2999";
 
 /// Struct that represents a connection to the in game api client
-/// Because the URL is constant, this is a zero sized struct to help organize code
-/// Please note, well this is currently 0 sized, it could change
-/// So it is advised to use `GameClient::new()` instead
-pub struct GameClient;
+///
+/// This points at `URL` unless another address is given, such as a mock server
+#[derive(Debug, Clone)]
+pub struct GameClient {
+    url: Cow<'static, str>,
+}
 
 impl GameClient {
     #[must_use]
     pub fn new() -> GameClient {
-        GameClient
+        Self::with_url(URL)
+    }
+
+    #[must_use]
+    /// Points the client at another address, in the same `host:port` format as `URL`
+    pub fn with_url(url: impl Into<Cow<'static, str>>) -> GameClient {
+        GameClient { url: url.into() }
     }
 
     #[must_use]
-    /// Returns the url, which is currently static
+    /// Returns the url, which is `URL` by default
     pub fn url(&self) -> &str {
-        URL
+        &self.url
     }
 
     /// This makes a head request to the in game API
@@ -52,7 +67,7 @@ impl GameClient {
     ) -> Result<Response<Incoming>, Error> {
         request_client
             .raw_request_template(
-                URL,
+                &self.url,
                 endpoint,
                 "HEAD",
                 None::<()>,
@@ -248,17 +263,15 @@ impl GameClient {
         riot_id: Option<&str>,
         request_client: &RequestClient,
     ) -> Result<R, Error> {
-        use hyper::body::Buf;
-
         let endpoint = if let Some(riot_id) = riot_id {
             format!("/liveclientdata/{endpoint}?riotId={riot_id}")
         } else {
             format!("/liveclientdata/{endpoint}")
         };
 
-        let buf = request_client
+        let (status, buf) = request_client
             .request_template(
-                URL,
+                &self.url,
                 &endpoint,
                 "GET",
                 None::<()>,
@@ -267,12 +280,179 @@ impl GameClient {
             )
             .await?;
 
-        Ok(serde_json::from_reader(buf.reader())?)
+        parse_response(status, buf, &SerializeFormat::Json)
+    }
+}
+
+/// The error code sent with a 404 until the loading screen ends
+const LOADING_ERROR_CODE: &str = "RESOURCE_NOT_FOUND";
+
+/// Deserializes a response from the in game or replay API, turning
+/// error statuses and error bodies into `Error::Game`
+///
+/// The body is only checked for an error after failing to deserialize,
+/// so successful responses are only parsed once
+pub(crate) fn parse_response<R: DeserializeOwned>(
+    status: StatusCode,
+    mut buf: impl Buf,
+    format: &SerializeFormat,
+) -> Result<R, Error> {
+    let body = buf.copy_to_bytes(buf.remaining());
+
+    if !status.is_success() {
+        return Err(api_error(Api::Game, status, &body));
+    }
+
+    let result = if format.is_json() {
+        serde_json::from_slice(&body).map_err(Error::from)
+    } else {
+        rmp_serde::from_slice(&body).map_err(Error::from)
+    };
+
+    result.map_err(|err| {
+        ErrorBody::parse(&body).map_or(err, |error| error.into_error(Api::Game, status))
+    })
+}
+
+/// Connection errors, and the error body sent while loading,
+/// both mean there is no game to read data from right now
+///
+/// Any other error from the API is a real failure, and is not matched
+pub(crate) fn is_unavailable(error: &Error) -> bool {
+    match error {
+        Error::HyperClientError(_) | Error::HyperError(_) => true,
+        Error::Game {
+            error_code,
+            http_status,
+            ..
+        } => *http_status == StatusCode::NOT_FOUND.as_u16() && error_code == LOADING_ERROR_CODE,
+        _ => false,
     }
 }
 
 impl Default for GameClient {
     fn default() -> Self {
-        GameClient
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{is_unavailable, parse_response};
+    use crate::in_game::types::GameData;
+    use crate::utils::requests::SerializeFormat;
+    use crate::Error;
+    use hyper::StatusCode;
+
+    #[test]
+    fn test_game_errors() {
+        const LOADING: &[u8] = br#"{"errorCode":"RESOURCE_NOT_FOUND","httpStatus":404,"implementationDetails":{},"message":"Invalid URI format"}"#;
+
+        let error =
+            parse_response::<GameData>(StatusCode::NOT_FOUND, LOADING, &SerializeFormat::Json)
+                .unwrap_err();
+        assert_eq!(error.error_code(), Some("RESOURCE_NOT_FOUND"));
+
+        // The error body is detected even when the status claims to be successful
+        let error = parse_response::<GameData>(StatusCode::OK, LOADING, &SerializeFormat::Json)
+            .unwrap_err();
+        assert!(matches!(
+            error,
+            Error::Game {
+                http_status: 404,
+                ..
+            }
+        ));
+
+        assert!(is_unavailable(&error));
+
+        // Anything else is still a schema mismatch
+        let error = parse_response::<GameData>(StatusCode::OK, &b"{}"[..], &SerializeFormat::Json)
+            .unwrap_err();
+        assert!(matches!(error, Error::SerdeJsonError(_)));
+        assert!(!is_unavailable(&error));
+
+        // Other failures are not mistaken for the game loading
+        let error = parse_response::<GameData>(
+            StatusCode::INTERNAL_SERVER_ERROR,
+            &b"oops"[..],
+            &SerializeFormat::Json,
+        )
+        .unwrap_err();
+        assert!(!is_unavailable(&error));
+
+        let bad_request = br#"{"errorCode":"BAD_REQUEST","httpStatus":400,"implementationDetails":{},"message":"Bad request"}"#;
+        let error = parse_response::<GameData>(
+            StatusCode::BAD_REQUEST,
+            &bad_request[..],
+            &SerializeFormat::Json,
+        )
+        .unwrap_err();
+        assert!(!is_unavailable(&error));
+
+        let game_data = br#"{"gameMode":"CLASSIC","gameTime":12.5,"mapName":"Map11","mapNumber":11,"mapTerrain":"Default"}"#;
+        let game_data =
+            parse_response::<GameData>(StatusCode::OK, &game_data[..], &SerializeFormat::Json)
+                .unwrap();
+        assert_eq!(game_data.map_number(), 11);
+    }
+
+    #[cfg(feature = "mock_game")]
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_mock_game_client() {
+        use crate::in_game::types::TeamID;
+        use crate::mock::game::{sample_all_game_data, MockGameServer};
+        use serde_json::json;
+
+        let first = sample_all_game_data();
+        let mut second = first.clone();
+        second["gameData"]["gameTime"] = json!(70.5);
+        second["events"]["Events"]
+            .as_array_mut()
+            .unwrap()
+            .push(json!({ "EventID": 1, "EventName": "MinionsSpawning", "EventTime": 65.0 }));
+
+        let server = MockGameServer::builder()
+            .timeline([first, second])
+            .start()
+            .await
+            .unwrap();
+        let client = server.game_client();
+        let request_client = server.request_client();
+
+        let all_game_data = client.all_game_data(&request_client).await.unwrap();
+        assert_eq!(
+            all_game_data.active_player().unwrap().riot_id(),
+            "Irelia#EUW"
+        );
+        assert_eq!(all_game_data.all_players().len(), 2);
+
+        let chaos = client
+            .player_list(Some(TeamID::Chaos), &request_client)
+            .await
+            .unwrap();
+        assert_eq!(chaos.len(), 1);
+        assert_eq!(chaos[0].riot_id(), "Ahri#NA1");
+
+        let scores = client
+            .player_scores("Ahri%23NA1", &request_client)
+            .await
+            .unwrap();
+        assert_eq!(scores.kills(), 0);
+
+        assert!(server.advance());
+        assert!(!server.advance());
+
+        let game_stats = client.game_stats(&request_client).await.unwrap();
+        assert!((game_stats.game_time().as_seconds_f64() - 70.5).abs() < f64::EPSILON);
+
+        let events = client.event_data(Some(1), &request_client).await.unwrap();
+        let events = serde_json::to_value(events).unwrap();
+        assert_eq!(events["Events"].as_array().unwrap().len(), 1);
+
+        // Without a snapshot the server answers the same way as a loading game
+        server.set_game_data(None);
+        let error = client.game_stats(&request_client).await.unwrap_err();
+        assert_eq!(error.error_code(), Some("RESOURCE_NOT_FOUND"));
     }
 }
@@ -0,0 +1,344 @@
+//! Events that the event feed leaves out, worked out by comparing two `allgamedata` snapshots
+//!
+//! Players are matched by their riot ID, and every event is stamped with the game time
+//! of the newer snapshot, so it is only as precise as the interval between the two
+
+use time::Duration;
+
+use crate::in_game::types::{Ability, AllGameData, AllPlayer, Item, SummonerSpell};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+/// The abilities that can be leveled up
+pub enum AbilitySlot {
+    Q,
+    W,
+    E,
+    R,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+/// A change to a single player between two snapshots
+pub enum DerivedEvent {
+    /// The player gained the item, `count` is how many were added to the stack
+    ItemPurchased { item: Item, count: u8 },
+    /// The player lost the item, which the API does not tell apart from the item
+    /// being used up, or being built into another one
+    ItemSold { item: Item, count: u8 },
+    /// The player reached a new level, which can skip levels if several were gained at once
+    LevelUp { level: u8 },
+    /// An ability was leveled up, this is only sent for the active player
+    AbilityLevelUp { slot: AbilitySlot, level: u8 },
+    /// The player died, and will respawn once the timer runs out
+    Died { respawn_timer: Duration },
+    /// The player is alive again, either because the timer ran out or from a revive
+    Respawned,
+    /// The skin changed, which happens for champions that transform
+    SkinChanged {
+        skin_id: i64,
+        skin_name: Option<Box<str>>,
+    },
+    /// A summoner spell was replaced, `slot` is 0 for the first spell and 1 for the second
+    SummonerSpellChanged { slot: usize, spell: SummonerSpell },
+}
+
+#[derive(Debug, Clone, PartialEq)]
+/// A `DerivedEvent`, along with who it happened to and when it was noticed
+pub struct TimedEvent {
+    game_time: Duration,
+    riot_id: Box<str>,
+    event: DerivedEvent,
+}
+
+impl TimedEvent {
+    #[must_use]
+    /// The game time of the snapshot the change was first seen in
+    pub fn game_time(&self) -> Duration {
+        self.game_time
+    }
+    #[must_use]
+    pub fn riot_id(&self) -> &str {
+        &self.riot_id
+    }
+    #[must_use]
+    pub fn event(&self) -> &DerivedEvent {
+        &self.event
+    }
+    #[must_use]
+    pub fn into_event(self) -> DerivedEvent {
+        self.event
+    }
+}
+
+#[must_use]
+/// Compares two snapshots of the same game, returning every change in the order
+/// the players are listed, and for each player in the order of the `DerivedEvent` variants
+///
+/// Players that are missing from either snapshot are skipped
+pub fn diff(previous: &AllGameData, next: &AllGameData) -> Vec<TimedEvent> {
+    let game_time = next.game_data().game_time();
+    let mut events = Vec::new();
+
+    for player in next.all_players() {
+        let Some(old) = previous
+            .all_players()
+            .iter()
+            .find(|old| old.riot_id() == player.riot_id())
+        else {
+            continue;
+        };
+
+        let mut push = |event| {
+            events.push(TimedEvent {
+                game_time,
+                riot_id: player.riot_id().into(),
+                event,
+            });
+        };
+
+        diff_items(old, player, &mut push);
+
+        if player.level() > old.level() {
+            push(DerivedEvent::LevelUp {
+                level: player.level(),
+            });
+        }
+
+        if let (Some(old_active), Some(active)) = (previous.active_player(), next.active_player()) {
+            if active.riot_id() == player.riot_id() && old_active.riot_id() == player.riot_id() {
+                let (old, new) = (old_active.abilities(), active.abilities());
+                let slots: [(AbilitySlot, &Ability, &Ability); 4] = [
+                    (AbilitySlot::Q, old.q(), new.q()),
+                    (AbilitySlot::W, old.w(), new.w()),
+                    (AbilitySlot::E, old.e(), new.e()),
+                    (AbilitySlot::R, old.r(), new.r()),
+                ];
+
+                for (slot, old, new) in slots {
+                    if new.ability_level() > old.ability_level() {
+                        push(DerivedEvent::AbilityLevelUp {
+                            slot,
+                            level: new.ability_level(),
+                        });
+                    }
+                }
+            }
+        }
+
+        match (old.is_dead(), player.is_dead()) {
+            (false, true) => push(DerivedEvent::Died {
+                respawn_timer: player.respawn_timer(),
+            }),
+            (true, false) => push(DerivedEvent::Respawned),
+            _ => {}
+        }
+
+        if player.skin_id() != old.skin_id() {
+            push(DerivedEvent::SkinChanged {
+                skin_id: player.skin_id(),
+                skin_name: player.skin_name().map(Into::into),
+            });
+        }
+
+        for slot in 0..2 {
+            let spell = &player.summoner_spells()[slot];
+            if spell.raw_display_name() != old.summoner_spells()[slot].raw_display_name() {
+                push(DerivedEvent::SummonerSpellChanged {
+                    slot,
+                    spell: spell.clone(),
+                });
+            }
+        }
+    }
+
+    events
+}
+
+/// Compares the items by ID and stack size, so moving an item to another slot is not a change
+fn diff_items(old: &AllPlayer, new: &AllPlayer, push: &mut impl FnMut(DerivedEvent)) {
+    let old_items = item_counts(old);
+    let new_items = item_counts(new);
+
+    let count_of = |items: &[(&Item, u8)], id| {
+        items
+            .iter()
+            .find(|(item, _)| item.item_id() == id)
+            .map_or(0, |(_, count)| *count)
+    };
+
+    for (item, count) in &new_items {
+        let old_count = count_of(&old_items, item.item_id());
+        if *count > old_count {
+            push(DerivedEvent::ItemPurchased {
+                item: (*item).clone(),
+                count: count - old_count,
+            });
+        }
+    }
+
+    for (item, count) in &old_items {
+        let new_count = count_of(&new_items, item.item_id());
+        if *count > new_count {
+            push(DerivedEvent::ItemSold {
+                item: (*item).clone(),
+                count: count - new_count,
+            });
+        }
+    }
+}
+
+/// The total stack size of every item ID, in the order the IDs first appear
+fn item_counts(player: &AllPlayer) -> Vec<(&Item, u8)> {
+    let mut counts: Vec<(&Item, u8)> = Vec::new();
+
+    for item in player.items().iter().flatten() {
+        match counts
+            .iter_mut()
+            .find(|(counted, _)| counted.item_id() == item.item_id())
+        {
+            Some((_, count)) => *count = count.saturating_add(item.count()),
+            None => counts.push((item, item.count())),
+        }
+    }
+
+    counts
+}
+
+/// Keeps the last snapshot, so each new one only has to be compared against it
+#[derive(Debug, Clone, Default)]
+pub struct SnapshotDiffer {
+    last: Option<AllGameData>,
+}
+
+impl SnapshotDiffer {
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Compares the snapshot against the last one, and keeps it for the next call
+    ///
+    /// Nothing is returned for the first snapshot, or when the game time goes
+    /// backwards, as that means a new game has started
+    pub fn update(&mut self, snapshot: AllGameData) -> Vec<TimedEvent> {
+        let events = match &self.last {
+            Some(last) if last.game_data().game_time() <= snapshot.game_data().game_time() => {
+                diff(last, &snapshot)
+            }
+            _ => Vec::new(),
+        };
+
+        self.last = Some(snapshot);
+        events
+    }
+
+    /// Forgets the last snapshot, such as when the game has ended
+    pub fn reset(&mut self) {
+        self.last = None;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{diff, AbilitySlot, DerivedEvent, SnapshotDiffer};
+    use crate::in_game::types::AllGameData;
+    use serde_json::{json, Value};
+    use time::Duration;
+
+    const ALL_GAME_DATA: &str = include_str!("../mock/allgamedata.json");
+
+    fn parse(value: Value) -> AllGameData {
+        serde_json::from_value(value).unwrap()
+    }
+
+    #[test]
+    fn test_diff_snapshots() {
+        let mut first: Value = serde_json::from_str(ALL_GAME_DATA).unwrap();
+        first["allPlayers"][1]["items"] = first["allPlayers"][0]["items"].clone();
+        let mut second = first.clone();
+        second["gameData"]["gameTime"] = json!(95.0);
+
+        let irelia = &mut second["allPlayers"][0];
+        let blade = irelia["items"][0].clone();
+        let mut potion = blade.clone();
+        potion["itemID"] = json!(2003);
+        potion["displayName"] = json!("Health Potion");
+        potion["count"] = json!(2);
+        potion["slot"] = json!(1);
+        // The blade moves slot, which is not a change
+        let mut moved_blade = blade.clone();
+        moved_blade["slot"] = json!(2);
+        irelia["items"] = json!([potion, moved_blade]);
+        irelia["level"] = json!(3);
+        irelia["isDead"] = json!(true);
+        irelia["respawnTimer"] = json!(10.0);
+        irelia["summonerSpells"]["summonerSpellTwo"] =
+            irelia["summonerSpells"]["summonerSpellOne"].clone();
+        second["activePlayer"]["abilities"]["W"]["abilityLevel"] = json!(1);
+
+        let ahri = &mut second["allPlayers"][1];
+        ahri["items"] = json!([]);
+        ahri["skinID"] = json!(7);
+
+        let events = diff(&parse(first.clone()), &parse(second.clone()));
+        assert!(events
+            .iter()
+            .all(|event| event.game_time() == Duration::seconds(95)));
+
+        let irelia: Vec<_> = events
+            .iter()
+            .filter(|event| event.riot_id() == "Irelia#EUW")
+            .map(|event| event.event().clone())
+            .collect();
+        assert!(matches!(
+            &irelia[0],
+            DerivedEvent::ItemPurchased { item, count: 2 } if item.item_id() == 2003
+        ));
+        assert_eq!(irelia[1], DerivedEvent::LevelUp { level: 3 });
+        assert_eq!(
+            irelia[2],
+            DerivedEvent::AbilityLevelUp {
+                slot: AbilitySlot::W,
+                level: 1
+            }
+        );
+        assert_eq!(
+            irelia[3],
+            DerivedEvent::Died {
+                respawn_timer: Duration::seconds(10)
+            }
+        );
+        assert!(matches!(
+            &irelia[4],
+            DerivedEvent::SummonerSpellChanged { slot: 1, spell } if spell.display_name() == "Flash"
+        ));
+        assert_eq!(irelia.len(), 5);
+
+        let ahri: Vec<_> = events
+            .iter()
+            .filter(|event| event.riot_id() == "Ahri#NA1")
+            .map(|event| event.event().clone())
+            .collect();
+        assert!(matches!(
+            &ahri[0],
+            DerivedEvent::ItemSold { item, count: 1 } if item.item_id() == 1055
+        ));
+        assert!(matches!(
+            ahri[1],
+            DerivedEvent::SkinChanged { skin_id: 7, .. }
+        ));
+        assert_eq!(ahri.len(), 2);
+
+        let mut differ = SnapshotDiffer::new();
+        assert!(differ.update(parse(first.clone())).is_empty());
+        assert_eq!(differ.update(parse(second.clone())), events);
+
+        // Respawning, then a new game which starts over from the beginning
+        let mut third = second.clone();
+        third["gameData"]["gameTime"] = json!(105.0);
+        third["allPlayers"][0]["isDead"] = json!(false);
+        let respawned = differ.update(parse(third));
+        assert_eq!(respawned.len(), 1);
+        assert_eq!(respawned[0].event(), &DerivedEvent::Respawned);
+        assert!(differ.update(parse(first)).is_empty());
+    }
+}
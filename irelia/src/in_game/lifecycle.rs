@@ -0,0 +1,281 @@
+//! Tracks whether there is a game, and which part of it is running
+//!
+//! The in game API is only up while the game is, and answers with errors until the
+//! loading screen ends, so the phase is worked out from which requests succeed,
+//! and from the `GameEnd` event, which is sent before the API shuts down
+
+use futures_util::Stream;
+
+use crate::in_game::stream::EventCursor;
+use crate::in_game::types::{EventDetails, GameData};
+use crate::in_game::{is_unavailable, GameClient};
+use crate::{Error, RequestClient};
+
+/// The endpoint used to check whether the API is up, and past the loading screen
+const HEAD_ENDPOINT: &str = "/liveclientdata/gamestats";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+/// The phases a game goes through, from the point of view of the in game API
+pub enum GamePhase {
+    /// The API is not running
+    NoGame,
+    /// The API is running, but answers with errors, as it does during the loading screen
+    Loading,
+    /// The API is serving data for a game that has not ended
+    InProgress,
+    /// A `GameEnd` event was sent, and the API has not shut down yet
+    Ended,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+/// Transitions emitted by `GameLifecycle`, each one entering the phase of the same name
+pub enum LifecycleEvent {
+    /// The API came up, or a new game began loading
+    Loading,
+    /// The game started, along with its stats at the time it was noticed
+    Started(GameData),
+    /// The game ended, with the result of the `GameEnd` event, such as `Win`
+    Ended { result: Box<str> },
+    /// The API shut down, either after the game ended or because it was closed
+    Closed,
+}
+
+impl LifecycleEvent {
+    #[must_use]
+    /// The phase the game is in after this transition
+    pub fn phase(&self) -> GamePhase {
+        match self {
+            LifecycleEvent::Loading => GamePhase::Loading,
+            LifecycleEvent::Started(_) => GamePhase::InProgress,
+            LifecycleEvent::Ended { .. } => GamePhase::Ended,
+            LifecycleEvent::Closed => GamePhase::NoGame,
+        }
+    }
+}
+
+/// Polls the in game API, keeping track of the phase it was in on the last poll
+#[derive(Debug, Clone)]
+pub struct GameLifecycle {
+    phase: GamePhase,
+    cursor: EventCursor,
+}
+
+impl GameLifecycle {
+    #[must_use]
+    /// Creates a lifecycle that assumes there is no game until the first poll
+    pub fn new() -> Self {
+        Self {
+            phase: GamePhase::NoGame,
+            cursor: EventCursor::new(),
+        }
+    }
+
+    #[must_use]
+    /// Returns the phase seen on the last poll
+    pub fn phase(&self) -> GamePhase {
+        self.phase
+    }
+
+    /// Queries the API once, returning an event if the phase changed since the last poll
+    ///
+    /// Phases can be skipped, such as going straight from `NoGame` to `InProgress`
+    /// when the game was already running before the first poll
+    ///
+    /// # Errors
+    /// This will return an error for anything other than the API being unavailable,
+    /// such as a response that cannot be deserialized
+    pub async fn poll(
+        &mut self,
+        game_client: &GameClient,
+        request_client: &RequestClient,
+    ) -> Result<Option<LifecycleEvent>, Error> {
+        let event = self.observe(game_client, request_client).await?;
+
+        let Some(event) = event else {
+            return Ok(None);
+        };
+
+        if event.phase() == self.phase {
+            return Ok(None);
+        }
+
+        #[cfg(feature = "tracing")]
+        tracing::info!(from = ?self.phase, to = ?event.phase(), "game phase changed");
+
+        self.phase = event.phase();
+        Ok(Some(event))
+    }
+
+    /// Works out the current phase, returning `None` when the game is still over
+    async fn observe(
+        &mut self,
+        game_client: &GameClient,
+        request_client: &RequestClient,
+    ) -> Result<Option<LifecycleEvent>, Error> {
+        let response = match game_client.head(HEAD_ENDPOINT, request_client).await {
+            Ok(response) => response,
+            Err(e) if is_unavailable(&e) => return Ok(self.lost(LifecycleEvent::Closed)),
+            Err(e) => return Err(e),
+        };
+
+        if !response.status().is_success() {
+            return Ok(self.lost(LifecycleEvent::Loading));
+        }
+
+        let game_data = match game_client.game_stats(request_client).await {
+            Ok(game_data) => game_data,
+            Err(e) if is_unavailable(&e) => return Ok(self.lost(LifecycleEvent::Loading)),
+            Err(e) => return Err(e),
+        };
+
+        let request_id = self.cursor.request_id();
+        let events = match game_client.event_data(request_id, request_client).await {
+            Ok(events) => events,
+            Err(e) if is_unavailable(&e) => return Ok(self.lost(LifecycleEvent::Loading)),
+            Err(e) => return Err(e),
+        };
+
+        let new_events = self.cursor.update(events);
+        let result = new_events
+            .into_iter()
+            .find_map(|event| match event.event_details() {
+                EventDetails::GameEnd { result } => Some(result.clone()),
+                _ => None,
+            });
+
+        Ok(match result {
+            Some(result) => Some(LifecycleEvent::Ended { result }),
+            // The stats screen keeps the API up after the game has ended
+            None if self.cursor.ended() => None,
+            None => Some(LifecycleEvent::Started(game_data)),
+        })
+    }
+
+    /// The events have to be read in full again, as the next game may have started in the meantime
+    ///
+    /// Until the events show that it has, errors after the game ended are only a blip
+    /// in the API of the stats screen, so the game doesn't go back to loading
+    fn lost(&mut self, event: LifecycleEvent) -> Option<LifecycleEvent> {
+        self.cursor.lost();
+
+        if self.phase == GamePhase::Ended && event == LifecycleEvent::Loading {
+            None
+        } else {
+            Some(event)
+        }
+    }
+}
+
+impl Default for GameLifecycle {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl GameClient {
+    /// Polls the API every interval, yielding every change of phase
+    ///
+    /// Nothing is yielded while there is no game, and the stream carries on
+    /// from one game to the next, so it never ends by itself
+    pub fn lifecycle_stream<'a>(
+        &'a self,
+        interval: std::time::Duration,
+        request_client: &'a RequestClient,
+    ) -> impl Stream<Item = Result<LifecycleEvent, Error>> + 'a {
+        let state = (GameLifecycle::new(), false);
+
+        futures_util::stream::unfold(state, move |(mut lifecycle, mut polled)| async move {
+            loop {
+                if polled {
+                    tokio::time::sleep(interval).await;
+                }
+                polled = true;
+
+                match lifecycle.poll(self, request_client).await {
+                    Ok(Some(event)) => return Some((Ok(event), (lifecycle, polled))),
+                    Ok(None) => {}
+                    Err(e) => return Some((Err(e), (lifecycle, polled))),
+                }
+            }
+        })
+    }
+}
+
+#[cfg(all(test, feature = "mock_game"))]
+mod tests {
+    use super::{GameLifecycle, GamePhase, LifecycleEvent};
+    use crate::mock::game::{sample_all_game_data, MockGameServer};
+    use serde_json::json;
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_mock_lifecycle() {
+        let mut ended = sample_all_game_data();
+        ended["events"]["Events"].as_array_mut().unwrap().push(
+            json!({ "EventID": 1, "EventName": "GameEnd", "EventTime": 900.0, "Result": "Win" }),
+        );
+
+        // No snapshot until the loading screen ends
+        let server = MockGameServer::builder().start().await.unwrap();
+        let client = server.game_client();
+        let request_client = server.request_client();
+
+        // Connections are pooled, so this one is kept unused until the server has gone away
+        let fresh_client = server.request_client();
+
+        let mut lifecycle = GameLifecycle::new();
+
+        assert_eq!(
+            lifecycle.poll(&client, &request_client).await.unwrap(),
+            Some(LifecycleEvent::Loading)
+        );
+        assert_eq!(
+            lifecycle.poll(&client, &request_client).await.unwrap(),
+            None
+        );
+
+        server.set_game_data(Some(sample_all_game_data()));
+        let Some(LifecycleEvent::Started(game_data)) =
+            lifecycle.poll(&client, &request_client).await.unwrap()
+        else {
+            panic!("the game should have started");
+        };
+        assert!((game_data.game_time().as_seconds_f64() - 12.5).abs() < f64::EPSILON);
+        assert_eq!(
+            lifecycle.poll(&client, &request_client).await.unwrap(),
+            None
+        );
+
+        server.set_game_data(Some(ended.clone()));
+        assert_eq!(
+            lifecycle.poll(&client, &request_client).await.unwrap(),
+            Some(LifecycleEvent::Ended {
+                result: "Win".into()
+            })
+        );
+        assert_eq!(
+            lifecycle.poll(&client, &request_client).await.unwrap(),
+            None
+        );
+
+        // The API of the same game failing for a moment doesn't start or end anything
+        server.set_game_data(None);
+        assert_eq!(
+            lifecycle.poll(&client, &request_client).await.unwrap(),
+            None
+        );
+        server.set_game_data(Some(ended));
+        assert_eq!(
+            lifecycle.poll(&client, &request_client).await.unwrap(),
+            None
+        );
+        assert_eq!(lifecycle.phase(), GamePhase::Ended);
+
+        // Nothing listens on the port once the server is gone
+        drop(server);
+        assert_eq!(
+            lifecycle.poll(&client, &fresh_client).await.unwrap(),
+            Some(LifecycleEvent::Closed)
+        );
+        assert_eq!(lifecycle.phase(), GamePhase::NoGame);
+    }
+}
@@ -0,0 +1,738 @@
+//! Tracks epic monster and inhibitor timers from the event feed
+//!
+//! The game only reports kills, so every timer is worked out from the time of the kill
+//! and a set of rules. The rules change between patches and differ between maps,
+//! which is why they can be replaced through `ObjectiveRules`
+
+use time::Duration;
+
+use crate::in_game::types::{
+    AllGameData, AllPlayer, DragonType, Event, EventDetails, MapName, MonsterKill, Structure,
+    TeamID,
+};
+
+#[derive(Debug, Clone, PartialEq)]
+/// When a monster spawns, and when it comes back after being killed
+pub struct SpawnRule {
+    first_spawn: Duration,
+    respawn: Option<Duration>,
+    despawn: Option<Duration>,
+}
+
+impl SpawnRule {
+    #[must_use]
+    /// A monster that first spawns at `first_spawn`, and comes back `respawn` after each kill,
+    /// or never if `respawn` is `None`
+    pub fn new(first_spawn: Duration, respawn: Option<Duration>) -> Self {
+        Self {
+            first_spawn,
+            respawn,
+            despawn: None,
+        }
+    }
+
+    #[must_use]
+    /// Removes the monster from the map at the given game time, even if it was never killed
+    pub fn despawn(mut self, despawn: Duration) -> Self {
+        self.despawn = Some(despawn);
+        self
+    }
+
+    #[must_use]
+    pub fn first_spawn(&self) -> Duration {
+        self.first_spawn
+    }
+    #[must_use]
+    pub fn respawn(&self) -> Option<Duration> {
+        self.respawn
+    }
+    #[must_use]
+    pub fn despawn_time(&self) -> Option<Duration> {
+        self.despawn
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+/// Timings used by `ObjectiveTracker`, a monster that is `None` never spawns
+pub struct ObjectiveRules {
+    dragon: Option<SpawnRule>,
+    elder_respawn: Duration,
+    dragons_for_soul: u8,
+    baron: Option<SpawnRule>,
+    herald: Option<SpawnRule>,
+    voidgrubs: Option<SpawnRule>,
+    voidgrubs_per_wave: u8,
+    voidgrub_waves: u8,
+    inhibitor_respawn: Duration,
+    baron_buff: Duration,
+    elder_buff: Duration,
+}
+
+impl ObjectiveRules {
+    #[must_use]
+    /// The rules on Summoner's Rift, as of season 2024
+    pub fn summoners_rift() -> Self {
+        Self {
+            dragon: Some(SpawnRule::new(
+                Duration::minutes(5),
+                Some(Duration::minutes(5)),
+            )),
+            elder_respawn: Duration::minutes(6),
+            dragons_for_soul: 4,
+            baron: Some(SpawnRule::new(
+                Duration::minutes(20),
+                Some(Duration::minutes(6)),
+            )),
+            herald: Some(
+                SpawnRule::new(Duration::minutes(14), None)
+                    .despawn(Duration::seconds(19 * 60 + 45)),
+            ),
+            voidgrubs: Some(
+                SpawnRule::new(Duration::minutes(5), Some(Duration::minutes(4)))
+                    .despawn(Duration::seconds(13 * 60 + 45)),
+            ),
+            voidgrubs_per_wave: 3,
+            voidgrub_waves: 2,
+            inhibitor_respawn: Duration::minutes(5),
+            baron_buff: Duration::minutes(3),
+            elder_buff: Duration::seconds(150),
+        }
+    }
+
+    #[must_use]
+    /// Maps without epic monsters, where only inhibitors are tracked
+    pub fn inhibitors_only(inhibitor_respawn: Duration) -> Self {
+        Self {
+            dragon: None,
+            baron: None,
+            herald: None,
+            voidgrubs: None,
+            inhibitor_respawn,
+            ..Self::summoners_rift()
+        }
+    }
+
+    #[must_use]
+    /// The default rules for the map, which only has inhibitors to track outside of Summoner's Rift
+    pub fn for_map(map: &MapName) -> Self {
+        match map {
+            MapName::SummonersRift | MapName::TutorialMap => Self::summoners_rift(),
+            _ => Self::inhibitors_only(Duration::minutes(5)),
+        }
+    }
+
+    #[must_use]
+    pub fn dragon(mut self, dragon: Option<SpawnRule>) -> Self {
+        self.dragon = dragon;
+        self
+    }
+    #[must_use]
+    /// How long after a soul is claimed, or after the last elder was killed, the next elder spawns
+    pub fn elder_respawn(mut self, elder_respawn: Duration) -> Self {
+        self.elder_respawn = elder_respawn;
+        self
+    }
+    #[must_use]
+    pub fn dragons_for_soul(mut self, dragons_for_soul: u8) -> Self {
+        self.dragons_for_soul = dragons_for_soul;
+        self
+    }
+    #[must_use]
+    pub fn baron(mut self, baron: Option<SpawnRule>) -> Self {
+        self.baron = baron;
+        self
+    }
+    #[must_use]
+    pub fn herald(mut self, herald: Option<SpawnRule>) -> Self {
+        self.herald = herald;
+        self
+    }
+    #[must_use]
+    /// The respawn of the rule is the time between the last grub of a wave dying and the next wave
+    pub fn voidgrubs(mut self, voidgrubs: Option<SpawnRule>) -> Self {
+        self.voidgrubs = voidgrubs;
+        self
+    }
+    #[must_use]
+    /// The game sends a `HordeKill` event for every grub, so this is how many events clear a wave
+    pub fn voidgrub_waves(mut self, per_wave: u8, waves: u8) -> Self {
+        self.voidgrubs_per_wave = per_wave;
+        self.voidgrub_waves = waves;
+        self
+    }
+    #[must_use]
+    pub fn inhibitor_respawn(mut self, inhibitor_respawn: Duration) -> Self {
+        self.inhibitor_respawn = inhibitor_respawn;
+        self
+    }
+    #[must_use]
+    pub fn baron_buff(mut self, baron_buff: Duration) -> Self {
+        self.baron_buff = baron_buff;
+        self
+    }
+    #[must_use]
+    pub fn elder_buff(mut self, elder_buff: Duration) -> Self {
+        self.elder_buff = elder_buff;
+        self
+    }
+}
+
+impl Default for ObjectiveRules {
+    fn default() -> Self {
+        Self::summoners_rift()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+/// Whether a monster is on the map, and if not, whether it is coming back
+pub enum ObjectiveState {
+    /// The monster will spawn at the given game time
+    Upcoming(Duration),
+    Alive,
+    /// The monster will not spawn again this game
+    Gone,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+/// A buff held by a team, from the kill that granted it until it runs out
+pub struct BuffWindow {
+    team: TeamID,
+    start: Duration,
+    end: Duration,
+}
+
+impl BuffWindow {
+    #[must_use]
+    /// The team of the killer, which is `TeamID::Unknown` if the killer is not a player
+    pub fn team(&self) -> &TeamID {
+        &self.team
+    }
+    #[must_use]
+    pub fn start(&self) -> Duration {
+        self.start
+    }
+    #[must_use]
+    pub fn end(&self) -> Duration {
+        self.end
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+/// A destroyed inhibitor, and when it comes back
+pub struct InhibitorTimer {
+    inhibitor: Structure,
+    respawn_at: Duration,
+}
+
+impl InhibitorTimer {
+    #[must_use]
+    pub fn inhibitor(&self) -> &Structure {
+        &self.inhibitor
+    }
+    #[must_use]
+    pub fn respawn_at(&self) -> Duration {
+        self.respawn_at
+    }
+    #[must_use]
+    /// The time left until the inhibitor respawns, which is never negative
+    pub fn remaining(&self, game_time: Duration) -> Duration {
+        (self.respawn_at - game_time).max(Duration::ZERO)
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+struct MonsterKills {
+    count: u8,
+    last: Option<(Duration, TeamID)>,
+}
+
+impl MonsterKills {
+    fn record(&mut self, time: Duration, team: TeamID) {
+        self.count = self.count.saturating_add(1);
+        self.last = Some((time, team));
+    }
+
+    fn last_time(&self) -> Option<Duration> {
+        self.last.as_ref().map(|(time, _)| *time)
+    }
+}
+
+/// Keeps the objective timers of one game up to date, from its events
+#[derive(Debug, Clone)]
+pub struct ObjectiveTracker {
+    rules: ObjectiveRules,
+    game_time: Duration,
+    next_event_id: i64,
+    /// The riot ID and game name of every player, as kill events use either, depending on the patch
+    players: Vec<(Box<str>, Box<str>, TeamID)>,
+    drakes: MonsterKills,
+    dragons: [Vec<DragonType>; 2],
+    soul: Option<(TeamID, DragonType, Duration)>,
+    elders: MonsterKills,
+    barons: MonsterKills,
+    heralds: MonsterKills,
+    voidgrubs: MonsterKills,
+    inhibitors: Vec<InhibitorTimer>,
+}
+
+impl ObjectiveTracker {
+    #[must_use]
+    pub fn new(rules: ObjectiveRules) -> Self {
+        Self {
+            rules,
+            game_time: Duration::ZERO,
+            next_event_id: 0,
+            players: Vec::new(),
+            drakes: MonsterKills::default(),
+            dragons: [Vec::new(), Vec::new()],
+            soul: None,
+            elders: MonsterKills::default(),
+            barons: MonsterKills::default(),
+            heralds: MonsterKills::default(),
+            voidgrubs: MonsterKills::default(),
+            inhibitors: Vec::new(),
+        }
+    }
+
+    #[must_use]
+    /// Creates a tracker with the default rules for the map
+    pub fn for_map(map: &MapName) -> Self {
+        Self::new(ObjectiveRules::for_map(map))
+    }
+
+    #[must_use]
+    pub fn rules(&self) -> &ObjectiveRules {
+        &self.rules
+    }
+
+    #[must_use]
+    /// The game time the timers are relative to
+    pub fn game_time(&self) -> Duration {
+        self.game_time
+    }
+
+    /// Sets the clock, which otherwise only moves forward with the events that are recorded
+    pub fn set_game_time(&mut self, game_time: Duration) {
+        self.game_time = game_time;
+    }
+
+    /// Sets the players kills are attributed to, as kill events only name the killer
+    pub fn set_players(&mut self, players: &[AllPlayer]) {
+        self.players = players
+            .iter()
+            .map(|player| {
+                (
+                    player.riot_id().into(),
+                    player.game_name().into(),
+                    player.team().clone(),
+                )
+            })
+            .collect();
+    }
+
+    /// Takes in a snapshot, updating the players and the clock, and recording any new events
+    pub fn update(&mut self, data: &AllGameData) {
+        self.set_players(data.all_players());
+        self.set_game_time(data.game_data().game_time());
+
+        for event in data.events().events() {
+            self.record(event);
+        }
+    }
+
+    /// Records a single event, events that were already recorded are ignored,
+    /// so the same list can be passed in more than once
+    pub fn record(&mut self, event: &Event) {
+        if event.event_id() < self.next_event_id {
+            return;
+        }
+        self.next_event_id = event.event_id() + 1;
+
+        let time = event.event_time();
+        self.game_time = self.game_time.max(time);
+        self.inhibitors.retain(|timer| timer.respawn_at > time);
+
+        match event.event_details() {
+            EventDetails::DragonKill {
+                dragon_type: DragonType::Elder,
+                kill_info,
+            } => {
+                let team = self.team_of(kill_info);
+                self.elders.record(time, team);
+            }
+            EventDetails::DragonKill {
+                dragon_type,
+                kill_info,
+            } => {
+                let team = self.team_of(kill_info);
+                self.drakes.record(time, team.clone());
+
+                // The timer still restarts when the killer can't be matched to a team
+                let Some(index) = team_index(&team) else {
+                    return;
+                };
+
+                self.dragons[index].push(dragon_type.clone());
+
+                if self.soul.is_none()
+                    && self.dragons[index].len() >= usize::from(self.rules.dragons_for_soul)
+                {
+                    self.soul = Some((team, dragon_type.clone(), time));
+                }
+            }
+            EventDetails::BaronKill(kill_info) => {
+                let team = self.team_of(kill_info);
+                self.barons.record(time, team);
+            }
+            EventDetails::HeraldKill(kill_info) => {
+                let team = self.team_of(kill_info);
+                self.heralds.record(time, team);
+            }
+            EventDetails::HordeKill(kill_info) => {
+                let team = self.team_of(kill_info);
+                self.voidgrubs.record(time, team);
+            }
+            EventDetails::InhibKilled { inhib_killed, .. } => {
+                self.inhibitors
+                    .retain(|timer| timer.inhibitor != *inhib_killed);
+                self.inhibitors.push(InhibitorTimer {
+                    inhibitor: inhib_killed.clone(),
+                    respawn_at: time + self.rules.inhibitor_respawn,
+                });
+            }
+            EventDetails::InhibRespawned { inhib_respawned } => {
+                self.inhibitors
+                    .retain(|timer| timer.inhibitor != *inhib_respawned);
+            }
+            _ => {}
+        }
+    }
+
+    fn team_of(&self, kill: &MonsterKill) -> TeamID {
+        let killer = kill.kill_info().killer_name();
+
+        self.players
+            .iter()
+            .find(|(riot_id, game_name, _)| &**riot_id == killer || &**game_name == killer)
+            .map_or(TeamID::Unknown, |(_, _, team)| team.clone())
+    }
+
+    #[must_use]
+    /// The elemental drakes stop spawning once a team has claimed a soul
+    pub fn dragon(&self) -> ObjectiveState {
+        let Some(rule) = &self.rules.dragon else {
+            return ObjectiveState::Gone;
+        };
+
+        if self.soul.is_some() {
+            return ObjectiveState::Gone;
+        }
+
+        self.state(rule, self.drakes.last_time())
+    }
+
+    #[must_use]
+    /// The elder dragon only spawns after a soul has been claimed
+    pub fn elder(&self) -> ObjectiveState {
+        if self.rules.dragon.is_none() {
+            return ObjectiveState::Gone;
+        }
+
+        let Some((_, _, soul_time)) = &self.soul else {
+            return ObjectiveState::Gone;
+        };
+
+        let respawn_from = self.elders.last_time().unwrap_or(*soul_time);
+        let spawn = respawn_from + self.rules.elder_respawn;
+
+        if self.game_time < spawn {
+            ObjectiveState::Upcoming(spawn)
+        } else {
+            ObjectiveState::Alive
+        }
+    }
+
+    #[must_use]
+    pub fn baron(&self) -> ObjectiveState {
+        self.rules
+            .baron
+            .as_ref()
+            .map_or(ObjectiveState::Gone, |rule| {
+                self.state(rule, self.barons.last_time())
+            })
+    }
+
+    #[must_use]
+    pub fn herald(&self) -> ObjectiveState {
+        self.rules
+            .herald
+            .as_ref()
+            .map_or(ObjectiveState::Gone, |rule| {
+                self.state(rule, self.heralds.last_time())
+            })
+    }
+
+    #[must_use]
+    /// A wave of voidgrubs stays alive until every grub in it has been killed
+    pub fn voidgrubs(&self) -> ObjectiveState {
+        let Some(rule) = &self.rules.voidgrubs else {
+            return ObjectiveState::Gone;
+        };
+
+        let per_wave = self.rules.voidgrubs_per_wave.max(1);
+        let kills = self.voidgrubs.count;
+
+        if kills >= per_wave.saturating_mul(self.rules.voidgrub_waves) {
+            return ObjectiveState::Gone;
+        }
+
+        if !kills.is_multiple_of(per_wave) {
+            return if self.despawned(rule) {
+                ObjectiveState::Gone
+            } else {
+                ObjectiveState::Alive
+            };
+        }
+
+        self.state(rule, self.voidgrubs.last_time())
+    }
+
+    fn despawned(&self, rule: &SpawnRule) -> bool {
+        rule.despawn
+            .is_some_and(|despawn| self.game_time >= despawn)
+    }
+
+    /// Works out the state of a monster from when it was last killed
+    fn state(&self, rule: &SpawnRule, last_kill: Option<Duration>) -> ObjectiveState {
+        if self.despawned(rule) {
+            return ObjectiveState::Gone;
+        }
+
+        let spawn = match last_kill {
+            Some(last_kill) => match rule.respawn {
+                Some(respawn) => last_kill + respawn,
+                None => return ObjectiveState::Gone,
+            },
+            None => rule.first_spawn,
+        };
+
+        if self.game_time < spawn {
+            ObjectiveState::Upcoming(spawn)
+        } else {
+            ObjectiveState::Alive
+        }
+    }
+
+    #[must_use]
+    /// The elemental drakes taken by the team, in the order they were taken
+    pub fn dragons(&self, team: &TeamID) -> &[DragonType] {
+        team_index(team).map_or(&[], |index| &self.dragons[index])
+    }
+
+    #[must_use]
+    /// How many drakes the team has towards a soul, capped at the number needed
+    pub fn soul_progress(&self, team: &TeamID) -> u8 {
+        let taken = u8::try_from(self.dragons(team).len()).unwrap_or(u8::MAX);
+        taken.min(self.rules.dragons_for_soul)
+    }
+
+    #[must_use]
+    /// The team that claimed a soul, and the type of the drake that completed it
+    pub fn soul(&self) -> Option<(&TeamID, &DragonType)> {
+        self.soul.as_ref().map(|(team, dragon, _)| (team, dragon))
+    }
+
+    #[must_use]
+    /// The inhibitors that are currently down
+    pub fn inhibitors(&self) -> Vec<InhibitorTimer> {
+        self.inhibitors
+            .iter()
+            .filter(|timer| timer.respawn_at > self.game_time)
+            .cloned()
+            .collect()
+    }
+
+    #[must_use]
+    /// The baron buff from the last baron kill, if it has not run out yet
+    ///
+    /// This is the longest the buff can last, as it is lost on death
+    pub fn baron_buff(&self) -> Option<BuffWindow> {
+        self.buff(&self.barons, self.rules.baron_buff)
+    }
+
+    #[must_use]
+    /// The elder buff from the last elder kill, if it has not run out yet
+    ///
+    /// This is the longest the buff can last, as it is lost on death
+    pub fn elder_buff(&self) -> Option<BuffWindow> {
+        self.buff(&self.elders, self.rules.elder_buff)
+    }
+
+    fn buff(&self, kills: &MonsterKills, length: Duration) -> Option<BuffWindow> {
+        let (start, team) = kills.last.as_ref()?;
+        let end = *start + length;
+
+        (self.game_time < end).then(|| BuffWindow {
+            team: team.clone(),
+            start: *start,
+            end,
+        })
+    }
+}
+
+fn team_index(team: &TeamID) -> Option<usize> {
+    match team {
+        TeamID::Order => Some(0),
+        TeamID::Chaos => Some(1),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ObjectiveRules, ObjectiveState, ObjectiveTracker};
+    use crate::in_game::types::{AllGameData, DragonType, Event, MapName, TeamID};
+    use serde_json::{json, Value};
+    use time::Duration;
+
+    const ALL_GAME_DATA: &str = include_str!("../mock/allgamedata.json");
+
+    fn event(id: i64, time: f64, name: &str, details: Value) -> Event {
+        let mut event = json!({ "EventID": id, "EventName": name, "EventTime": time });
+        if let (Some(event), Value::Object(details)) = (event.as_object_mut(), details) {
+            event.extend(details);
+        }
+        serde_json::from_value(event).unwrap()
+    }
+
+    fn monster(killer: &str) -> Value {
+        json!({ "KillerName": killer, "Assisters": [], "Stolen": "False" })
+    }
+
+    fn dragon(id: i64, time: f64, dragon_type: &str) -> Event {
+        let mut details = monster("Irelia");
+        details["DragonType"] = json!(dragon_type);
+        event(id, time, "DragonKill", details)
+    }
+
+    #[test]
+    fn test_objective_tracker() {
+        let data: AllGameData = serde_json::from_str(ALL_GAME_DATA).unwrap();
+        let mut tracker = ObjectiveTracker::for_map(data.game_data().map_name());
+        tracker.update(&data);
+
+        assert_eq!(
+            tracker.dragon(),
+            ObjectiveState::Upcoming(Duration::minutes(5))
+        );
+        assert_eq!(
+            tracker.baron(),
+            ObjectiveState::Upcoming(Duration::minutes(20))
+        );
+        assert_eq!(
+            tracker.herald(),
+            ObjectiveState::Upcoming(Duration::minutes(14))
+        );
+        assert_eq!(
+            tracker.voidgrubs(),
+            ObjectiveState::Upcoming(Duration::minutes(5))
+        );
+
+        // Grubs are killed one at a time, the wave is only cleared by the last one
+        tracker.record(&event(1, 400.0, "HordeKill", monster("Ahri#NA1")));
+        tracker.record(&event(2, 401.0, "HordeKill", monster("Ahri#NA1")));
+        assert_eq!(tracker.voidgrubs(), ObjectiveState::Alive);
+        tracker.record(&event(3, 402.0, "HordeKill", monster("Ahri#NA1")));
+        assert_eq!(
+            tracker.voidgrubs(),
+            ObjectiveState::Upcoming(Duration::seconds(642))
+        );
+
+        tracker.record(&dragon(4, 330.0, "Fire"));
+        assert_eq!(
+            tracker.dragon(),
+            ObjectiveState::Upcoming(Duration::seconds(630))
+        );
+        tracker.record(&dragon(5, 660.0, "Earth"));
+        tracker.record(&dragon(6, 990.0, "Water"));
+        assert_eq!(tracker.soul_progress(&TeamID::Order), 3);
+        assert_eq!(tracker.elder(), ObjectiveState::Gone);
+
+        tracker.record(&dragon(7, 1320.0, "Water"));
+        assert_eq!(tracker.soul(), Some((&TeamID::Order, &DragonType::Water)));
+        assert_eq!(tracker.soul_progress(&TeamID::Chaos), 0);
+        assert_eq!(tracker.dragon(), ObjectiveState::Gone);
+        assert_eq!(
+            tracker.elder(),
+            ObjectiveState::Upcoming(Duration::seconds(1680))
+        );
+
+        tracker.record(&event(8, 1600.0, "BaronKill", monster("Ahri")));
+        let inhibitor =
+            json!({ "InhibKilled": "Barracks_T2_R1", "KillerName": "Irelia", "Assisters": [] });
+        tracker.record(&event(9, 1650.0, "InhibKilled", inhibitor));
+        tracker.record(&dragon(10, 1700.0, "Elder"));
+
+        // Events that were already recorded are skipped
+        tracker.record(&dragon(6, 990.0, "Water"));
+        assert_eq!(tracker.dragons(&TeamID::Order).len(), 4);
+
+        assert_eq!(tracker.game_time(), Duration::seconds(1700));
+        assert_eq!(tracker.herald(), ObjectiveState::Gone);
+        assert_eq!(tracker.voidgrubs(), ObjectiveState::Gone);
+        assert_eq!(
+            tracker.baron(),
+            ObjectiveState::Upcoming(Duration::seconds(1960))
+        );
+        assert_eq!(
+            tracker.elder(),
+            ObjectiveState::Upcoming(Duration::seconds(2060))
+        );
+
+        let baron_buff = tracker.baron_buff().unwrap();
+        assert_eq!(baron_buff.team(), &TeamID::Chaos);
+        assert_eq!(baron_buff.end(), Duration::seconds(1780));
+        let elder_buff = tracker.elder_buff().unwrap();
+        assert_eq!(elder_buff.team(), &TeamID::Order);
+        assert_eq!(elder_buff.end(), Duration::seconds(1850));
+
+        let inhibitors = tracker.inhibitors();
+        assert_eq!(inhibitors.len(), 1);
+        assert!(inhibitors[0].inhibitor().is_red_side());
+        assert_eq!(
+            inhibitors[0].remaining(tracker.game_time()),
+            Duration::seconds(250)
+        );
+
+        tracker.set_game_time(Duration::seconds(1960));
+        assert!(tracker.baron_buff().is_none());
+        assert!(tracker.elder_buff().is_none());
+        assert!(tracker.inhibitors().is_empty());
+        assert_eq!(tracker.baron(), ObjectiveState::Alive);
+    }
+
+    #[test]
+    fn test_objective_rules() {
+        let aram = ObjectiveTracker::for_map(&MapName::HowlingAbyss);
+        assert_eq!(aram.dragon(), ObjectiveState::Gone);
+        assert_eq!(aram.baron(), ObjectiveState::Gone);
+
+        // A patch that moves the baron, and only has one wave of grubs
+        let rules = ObjectiveRules::summoners_rift()
+            .baron(Some(super::SpawnRule::new(
+                Duration::minutes(25),
+                Some(Duration::minutes(6)),
+            )))
+            .voidgrub_waves(3, 1);
+        let mut tracker = ObjectiveTracker::new(rules);
+        assert_eq!(
+            tracker.baron(),
+            ObjectiveState::Upcoming(Duration::minutes(25))
+        );
+
+        for id in 0..3 {
+            tracker.record(&event(id, 400.0, "HordeKill", monster("Irelia")));
+        }
+        assert_eq!(tracker.voidgrubs(), ObjectiveState::Gone);
+    }
+}
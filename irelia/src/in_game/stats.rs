@@ -0,0 +1,344 @@
+//! Team and player totals computed from `AllGameData`, as shown on the scoreboard
+//!
+//! Gold is only an estimate, as the API does not expose it for other players,
+//! so it is summed from the price of the items each player holds
+
+use crate::in_game::types::{AllGameData, AllPlayer, Position, TeamID};
+
+/// The positions that have a matchup, in the order they are listed in the client
+const ROLES: [Position; 5] = [
+    Position::Top,
+    Position::Jungle,
+    Position::Middle,
+    Position::Bottom,
+    Position::Support,
+];
+
+#[derive(Debug, Clone, PartialEq)]
+/// The totals of every player on one team
+pub struct TeamStats {
+    team: TeamID,
+    players: u8,
+    kills: u16,
+    deaths: u16,
+    assists: u16,
+    creep_score: u32,
+    item_gold: u32,
+    total_level: u16,
+    ward_score: f64,
+}
+
+impl TeamStats {
+    #[must_use]
+    pub fn team(&self) -> &TeamID {
+        &self.team
+    }
+    #[must_use]
+    pub fn players(&self) -> u8 {
+        self.players
+    }
+    #[must_use]
+    pub fn kills(&self) -> u16 {
+        self.kills
+    }
+    #[must_use]
+    pub fn deaths(&self) -> u16 {
+        self.deaths
+    }
+    #[must_use]
+    pub fn assists(&self) -> u16 {
+        self.assists
+    }
+    #[must_use]
+    pub fn creep_score(&self) -> u32 {
+        self.creep_score
+    }
+    #[must_use]
+    /// The summed price of every item held by the team, see `AllPlayer::item_gold`
+    pub fn item_gold(&self) -> u32 {
+        self.item_gold
+    }
+    #[must_use]
+    pub fn total_level(&self) -> u16 {
+        self.total_level
+    }
+    #[must_use]
+    /// This is 0 for a team without players
+    pub fn average_level(&self) -> f64 {
+        if self.players == 0 {
+            return 0.0;
+        }
+        f64::from(self.total_level) / f64::from(self.players)
+    }
+    #[must_use]
+    pub fn ward_score(&self) -> f64 {
+        self.ward_score
+    }
+    #[must_use]
+    /// Kills and assists per death, counting no deaths as one
+    pub fn kda_ratio(&self) -> f64 {
+        kda_ratio(self.kills, self.deaths, self.assists)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+/// A player, along with the metrics that depend on the rest of the game
+pub struct PlayerStats<'a> {
+    player: &'a AllPlayer,
+    kda_ratio: f64,
+    cs_per_minute: f64,
+    kill_participation: f64,
+}
+
+impl<'a> PlayerStats<'a> {
+    #[must_use]
+    pub fn player(&self) -> &'a AllPlayer {
+        self.player
+    }
+    #[must_use]
+    /// Kills and assists per death, counting no deaths as one
+    pub fn kda_ratio(&self) -> f64 {
+        self.kda_ratio
+    }
+    #[must_use]
+    /// This is 0 before the first minute has passed
+    pub fn cs_per_minute(&self) -> f64 {
+        self.cs_per_minute
+    }
+    #[must_use]
+    /// The share of the team's kills the player took part in, between 0 and 1
+    ///
+    /// This is 0 while the team has no kills
+    pub fn kill_participation(&self) -> f64 {
+        self.kill_participation
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+/// The two players sharing a position, the differences are from the point of view of Order
+pub struct Matchup<'a> {
+    position: Position,
+    order: &'a AllPlayer,
+    chaos: &'a AllPlayer,
+}
+
+impl<'a> Matchup<'a> {
+    #[must_use]
+    pub fn position(&self) -> &Position {
+        &self.position
+    }
+    #[must_use]
+    pub fn order(&self) -> &'a AllPlayer {
+        self.order
+    }
+    #[must_use]
+    pub fn chaos(&self) -> &'a AllPlayer {
+        self.chaos
+    }
+    #[must_use]
+    pub fn level_difference(&self) -> i16 {
+        i16::from(self.order.level()) - i16::from(self.chaos.level())
+    }
+    #[must_use]
+    pub fn creep_score_difference(&self) -> i32 {
+        i32::from(self.order.scores().creep_score()) - i32::from(self.chaos.scores().creep_score())
+    }
+    #[must_use]
+    pub fn item_gold_difference(&self) -> i64 {
+        i64::from(self.order.item_gold()) - i64::from(self.chaos.item_gold())
+    }
+}
+
+impl AllPlayer {
+    #[must_use]
+    /// The summed price of every item the player holds, including stacks and the trinket
+    ///
+    /// This is an estimate of the gold spent, as it leaves out consumed and sold items
+    pub fn item_gold(&self) -> u32 {
+        self.items()
+            .iter()
+            .flatten()
+            .map(|item| u32::from(item.price()) * u32::from(item.count()))
+            .sum()
+    }
+}
+
+impl AllGameData {
+    #[must_use]
+    /// The totals of every player on the team
+    pub fn team_stats(&self, team: &TeamID) -> TeamStats {
+        let mut stats = TeamStats {
+            team: team.clone(),
+            players: 0,
+            kills: 0,
+            deaths: 0,
+            assists: 0,
+            creep_score: 0,
+            item_gold: 0,
+            total_level: 0,
+            ward_score: 0.0,
+        };
+
+        for player in self
+            .all_players()
+            .iter()
+            .filter(|player| player.team() == team)
+        {
+            let scores = player.scores();
+
+            stats.players = stats.players.saturating_add(1);
+            stats.kills += u16::from(scores.kills());
+            stats.deaths += u16::from(scores.deaths());
+            stats.assists += u16::from(scores.assists());
+            stats.creep_score += u32::from(scores.creep_score());
+            stats.item_gold += player.item_gold();
+            stats.total_level += u16::from(player.level());
+            stats.ward_score += scores.ward_score();
+        }
+
+        stats
+    }
+
+    #[must_use]
+    /// The totals of Order and Chaos, in that order
+    pub fn teams(&self) -> [TeamStats; 2] {
+        [
+            self.team_stats(&TeamID::Order),
+            self.team_stats(&TeamID::Chaos),
+        ]
+    }
+
+    #[must_use]
+    /// The metrics of every player, in the same order as `all_players`
+    pub fn player_stats(&self) -> Vec<PlayerStats<'_>> {
+        let [order, chaos] = self.teams();
+        let minutes = self.game_data().game_time().as_seconds_f64() / 60.0;
+
+        self.all_players()
+            .iter()
+            .map(|player| {
+                let scores = player.scores();
+                let team_kills = match player.team() {
+                    TeamID::Order => order.kills,
+                    TeamID::Chaos => chaos.kills,
+                    _ => 0,
+                };
+
+                let cs_per_minute = if minutes >= 1.0 {
+                    f64::from(scores.creep_score()) / minutes
+                } else {
+                    0.0
+                };
+
+                let kill_participation = if team_kills == 0 {
+                    0.0
+                } else {
+                    let involved = u16::from(scores.kills()) + u16::from(scores.assists());
+                    (f64::from(involved) / f64::from(team_kills)).min(1.0)
+                };
+
+                PlayerStats {
+                    player,
+                    kda_ratio: kda_ratio(
+                        u16::from(scores.kills()),
+                        u16::from(scores.deaths()),
+                        u16::from(scores.assists()),
+                    ),
+                    cs_per_minute,
+                    kill_participation,
+                }
+            })
+            .collect()
+    }
+
+    #[must_use]
+    /// The players facing each other in every role, roles without a player on both teams,
+    /// such as every role in modes without positions, are left out
+    pub fn matchups(&self) -> Vec<Matchup<'_>> {
+        let find = |team: TeamID, position: &Position| {
+            self.all_players()
+                .iter()
+                .find(|player| *player.team() == team && player.position() == position)
+        };
+
+        ROLES
+            .into_iter()
+            .filter_map(|position| {
+                let order = find(TeamID::Order, &position)?;
+                let chaos = find(TeamID::Chaos, &position)?;
+
+                Some(Matchup {
+                    position,
+                    order,
+                    chaos,
+                })
+            })
+            .collect()
+    }
+}
+
+fn kda_ratio(kills: u16, deaths: u16, assists: u16) -> f64 {
+    f64::from(kills + assists) / f64::from(deaths.max(1))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::in_game::types::{AllGameData, Position, TeamID};
+    use serde_json::{json, Value};
+
+    const ALL_GAME_DATA: &str = include_str!("../mock/allgamedata.json");
+
+    #[test]
+    fn test_team_stats() {
+        let mut data: Value = serde_json::from_str(ALL_GAME_DATA).unwrap();
+        data["gameData"]["gameTime"] = json!(600.0);
+
+        let mut support = data["allPlayers"][0].clone();
+        support["riotId"] = json!("Sona#EUW");
+        support["position"] = json!("UTILITY");
+        support["items"] = json!([]);
+        support["level"] = json!(6);
+        support["scores"] =
+            json!({ "kills": 0, "deaths": 1, "assists": 3, "creepScore": 10, "wardScore": 12.5 });
+        data["allPlayers"].as_array_mut().unwrap().push(support);
+
+        data["allPlayers"][0]["level"] = json!(9);
+        data["allPlayers"][0]["scores"] =
+            json!({ "kills": 4, "deaths": 0, "assists": 0, "creepScore": 80, "wardScore": 3.0 });
+        data["allPlayers"][1]["level"] = json!(7);
+        data["allPlayers"][1]["position"] = json!("TOP");
+        data["allPlayers"][1]["scores"] =
+            json!({ "kills": 1, "deaths": 4, "assists": 0, "creepScore": 70, "wardScore": 2.0 });
+
+        let data: AllGameData = serde_json::from_value(data).unwrap();
+
+        let [order, chaos] = data.teams();
+        assert_eq!(order.players(), 2);
+        assert_eq!((order.kills(), order.deaths(), order.assists()), (4, 1, 3));
+        assert_eq!(order.creep_score(), 90);
+        assert_eq!(order.item_gold(), 450);
+        assert!((order.average_level() - 7.5).abs() < f64::EPSILON);
+        assert!((order.ward_score() - 15.5).abs() < f64::EPSILON);
+        assert!((order.kda_ratio() - 7.0).abs() < f64::EPSILON);
+        assert_eq!(chaos.team(), &TeamID::Chaos);
+        assert_eq!(chaos.item_gold(), 0);
+
+        let players = data.player_stats();
+        let irelia = &players[0];
+        assert_eq!(irelia.player().riot_id(), "Irelia#EUW");
+        assert!((irelia.kda_ratio() - 4.0).abs() < f64::EPSILON);
+        assert!((irelia.cs_per_minute() - 8.0).abs() < f64::EPSILON);
+        assert!((irelia.kill_participation() - 1.0).abs() < f64::EPSILON);
+        assert!((players[2].kill_participation() - 0.75).abs() < f64::EPSILON);
+        assert!((players[1].kda_ratio() - 0.25).abs() < f64::EPSILON);
+
+        // Only the top laners face each other, the support has no opponent
+        let matchups = data.matchups();
+        assert_eq!(matchups.len(), 1);
+        assert_eq!(matchups[0].position(), &Position::Top);
+        assert_eq!(matchups[0].chaos().riot_id(), "Ahri#NA1");
+        assert_eq!(matchups[0].level_difference(), 2);
+        assert_eq!(matchups[0].creep_score_difference(), 10);
+        assert_eq!(matchups[0].item_gold_difference(), 450);
+    }
+}
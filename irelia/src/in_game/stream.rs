@@ -0,0 +1,361 @@
+//! Polls `/liveclientdata/eventdata`, yielding every event exactly once
+//!
+//! The API only ever returns the full list of events, or the ones from an `eventID` onwards,
+//! so the cursor keeps track of the last event that was seen, and which game it belonged to
+
+use std::collections::VecDeque;
+
+use futures_util::Stream;
+use time::Duration;
+
+use crate::in_game::types::{Event, EventDetails, Events};
+use crate::in_game::{is_unavailable, GameClient};
+use crate::{Error, RequestClient};
+
+/// Keeps track of which events have been seen, across polls and across games
+///
+/// This is what `GameClient::event_stream` uses internally, and can be used
+/// to drive a custom poll loop instead
+#[derive(Debug, Clone)]
+pub struct EventCursor {
+    next_id: i64,
+    /// The time of the `GameStart` event of the current game, as its ID is always 0
+    game_start: Option<Duration>,
+    /// Set when the API has gone away, as a different game may be running when it comes back
+    resync: bool,
+    ended: bool,
+}
+
+impl EventCursor {
+    #[must_use]
+    pub fn new() -> Self {
+        Self {
+            next_id: 0,
+            game_start: None,
+            resync: true,
+            ended: false,
+        }
+    }
+
+    #[must_use]
+    /// The `eventID` to pass to `GameClient::event_data`
+    ///
+    /// This is `None` when the full list is needed, to tell whether a new game has started
+    pub fn request_id(&self) -> Option<i32> {
+        if self.resync {
+            None
+        } else {
+            i32::try_from(self.next_id).ok()
+        }
+    }
+
+    #[must_use]
+    /// Whether a `GameEnd` event has been seen, and no new game has started since
+    pub fn ended(&self) -> bool {
+        self.ended
+    }
+
+    /// Marks the API as gone, which happens while loading, and once the game has ended
+    pub fn lost(&mut self) {
+        self.resync = true;
+    }
+
+    /// Takes the reply to the last request, returning the events that haven't been seen yet
+    pub fn update(&mut self, events: Events) -> Vec<Event> {
+        let events = events.into_events();
+
+        if self.resync {
+            let game_start = events
+                .iter()
+                .find(|event| event.event_id() == 0)
+                .map(Event::event_time);
+
+            let last_id = events.iter().map(Event::event_id).max();
+
+            // The start time alone can match between games, so a game that has fewer events
+            // than were already seen is new as well. Having ended isn't enough by itself,
+            // as the API of the game that ended can stay up for a while
+            let new_game = last_id.is_none_or(|id| id + 1 < self.next_id)
+                || (self.game_start.is_some() && self.game_start != game_start);
+
+            if new_game {
+                self.next_id = 0;
+                self.ended = false;
+            }
+
+            self.game_start = game_start;
+            self.resync = false;
+        } else if self.game_start.is_none() {
+            self.game_start = events
+                .iter()
+                .find(|event| event.event_id() == 0)
+                .map(Event::event_time);
+        }
+
+        let new: Vec<Event> = events
+            .into_vec()
+            .into_iter()
+            .filter(|event| event.event_id() >= self.next_id)
+            .collect();
+
+        if let Some(last) = new.iter().map(Event::event_id).max() {
+            self.next_id = last + 1;
+        }
+
+        if new
+            .iter()
+            .any(|event| matches!(event.event_details(), EventDetails::GameEnd { .. }))
+        {
+            self.ended = true;
+        }
+
+        new
+    }
+}
+
+impl Default for EventCursor {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+struct State {
+    cursor: EventCursor,
+    pending: VecDeque<Event>,
+    polled: bool,
+}
+
+impl GameClient {
+    /// Polls the event data every interval, yielding each new event exactly once
+    ///
+    /// The stream waits through loading screens, and for the API to come back after a game
+    /// has ended, and starts from the first event again when a new game begins,
+    /// so it never ends by itself. Only errors other than the API being unavailable are yielded
+    ///
+    /// Requests are only sent while the stream is being polled, so a consumer that stops
+    /// polling for the whole gap between two games will not notice the new one
+    pub fn event_stream<'a>(
+        &'a self,
+        interval: std::time::Duration,
+        request_client: &'a RequestClient,
+    ) -> impl Stream<Item = Result<Event, Error>> + 'a {
+        let state = State {
+            cursor: EventCursor::new(),
+            pending: VecDeque::new(),
+            polled: false,
+        };
+
+        futures_util::stream::unfold(state, move |mut state| async move {
+            loop {
+                if let Some(event) = state.pending.pop_front() {
+                    return Some((Ok(event), state));
+                }
+
+                if state.polled {
+                    tokio::time::sleep(interval).await;
+                }
+                state.polled = true;
+
+                let request_id = state.cursor.request_id();
+
+                match self.event_data(request_id, request_client).await {
+                    Ok(events) => state.pending.extend(state.cursor.update(events)),
+                    Err(e) if is_unavailable(&e) => state.cursor.lost(),
+                    Err(e) => return Some((Err(e), state)),
+                }
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::EventCursor;
+    use crate::in_game::types::{Event, Events};
+    use serde_json::json;
+
+    fn events(events: &serde_json::Value) -> Events {
+        serde_json::from_value(json!({ "Events": events })).unwrap()
+    }
+
+    fn ids(events: &[Event]) -> Vec<i64> {
+        events.iter().map(Event::event_id).collect()
+    }
+
+    #[cfg(feature = "mock_game")]
+    async fn next(
+        stream: &mut (impl futures_util::Stream<Item = Result<Event, crate::Error>> + Unpin),
+    ) -> Event {
+        use futures_util::StreamExt;
+
+        tokio::time::timeout(std::time::Duration::from_secs(5), stream.next())
+            .await
+            .unwrap()
+            .unwrap()
+            .unwrap()
+    }
+
+    /// Takes the API away until the stream has noticed, then starts the next game
+    #[cfg(feature = "mock_game")]
+    async fn start_next_game(
+        server: &crate::mock::game::MockGameServer,
+        stream: &mut (impl futures_util::Stream<Item = Result<Event, crate::Error>> + Unpin),
+        snapshot: serde_json::Value,
+    ) {
+        use futures_util::StreamExt;
+
+        server.set_game_data(None);
+        assert!(
+            tokio::time::timeout(std::time::Duration::from_millis(50), stream.next())
+                .await
+                .is_err()
+        );
+        server.set_game_data(Some(snapshot));
+    }
+
+    #[test]
+    fn test_event_cursor() {
+        let start = json!({ "EventID": 0, "EventName": "GameStart", "EventTime": 0.5 });
+        let minions = json!({ "EventID": 1, "EventName": "MinionsSpawning", "EventTime": 65.0 });
+        let end =
+            json!({ "EventID": 2, "EventName": "GameEnd", "EventTime": 900.0, "Result": "Win" });
+
+        let mut cursor = EventCursor::new();
+        assert_eq!(cursor.request_id(), None);
+
+        let new = cursor.update(events(&json!([start])));
+        assert_eq!(ids(&new), [0]);
+        assert_eq!(cursor.request_id(), Some(1));
+
+        // Replies from the cursor onwards, overlapping the last poll
+        let new = cursor.update(events(&json!([start, minions])));
+        assert_eq!(ids(&new), [1]);
+
+        // The API going away in the middle of the same game doesn't repeat anything
+        cursor.lost();
+        assert_eq!(cursor.request_id(), None);
+        let new = cursor.update(events(&json!([start, minions, end])));
+        assert_eq!(ids(&new), [2]);
+        assert!(cursor.ended());
+
+        // A new game has a different start, so every event is new again
+        cursor.lost();
+        let new_start = json!({ "EventID": 0, "EventName": "GameStart", "EventTime": 0.7 });
+        let new = cursor.update(events(&json!([new_start])));
+        assert_eq!(ids(&new), [0]);
+        assert!(!cursor.ended());
+
+        // Loading into the next game, before it has started
+        cursor.lost();
+        assert!(cursor.update(events(&json!([]))).is_empty());
+        let new = cursor.update(events(&json!([start])));
+        assert_eq!(ids(&new), [0]);
+
+        // Games can start at the same time, so one with fewer events than were seen is new
+        assert_eq!(ids(&cursor.update(events(&json!([start, minions])))), [1]);
+        cursor.lost();
+        let new = cursor.update(events(&json!([start])));
+        assert_eq!(ids(&new), [0]);
+
+        // Losing the API after the game ended, while it is still the same game, repeats nothing
+        assert_eq!(
+            ids(&cursor.update(events(&json!([start, minions, end])))),
+            [1, 2]
+        );
+        cursor.lost();
+        assert!(cursor
+            .update(events(&json!([start, minions, end])))
+            .is_empty());
+        assert!(cursor.ended());
+
+        // But a game after it, with fewer events, starts over
+        cursor.lost();
+        let new = cursor.update(events(&json!([start, minions])));
+        assert_eq!(ids(&new), [0, 1]);
+        assert!(!cursor.ended());
+    }
+
+    #[cfg(feature = "mock_game")]
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_mock_event_stream() {
+        use crate::mock::game::{sample_all_game_data, MockGameServer};
+        use std::pin::pin;
+        use std::time::Duration;
+
+        let first = sample_all_game_data();
+        let mut second = first.clone();
+        second["events"]["Events"]
+            .as_array_mut()
+            .unwrap()
+            .push(json!({ "EventID": 1, "EventName": "MinionsSpawning", "EventTime": 65.0 }));
+        let mut next_game = first.clone();
+        next_game["events"]["Events"][0]["EventTime"] = json!(1.25);
+
+        let server = MockGameServer::builder()
+            .timeline([first, second])
+            .start()
+            .await
+            .unwrap();
+        let client = server.game_client();
+        let request_client = server.request_client();
+
+        let mut stream = pin!(client.event_stream(Duration::from_millis(10), &request_client));
+
+        assert_eq!(next(&mut stream).await.event_id(), 0);
+        assert!(server.advance());
+        assert_eq!(next(&mut stream).await.event_id(), 1);
+
+        // The game ends and the API goes away, then a new game starts
+        start_next_game(&server, &mut stream, next_game).await;
+
+        let event = next(&mut stream).await;
+        assert_eq!(event.event_id(), 0);
+        assert!((event.event_time().as_seconds_f64() - 1.25).abs() < f64::EPSILON);
+    }
+
+    #[cfg(feature = "mock_game")]
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_mock_event_stream_same_start() {
+        use crate::mock::game::{sample_all_game_data, MockGameServer};
+        use std::pin::pin;
+        use std::time::Duration;
+
+        // Every game starts at the same time, so only the events tell them apart
+        let started = sample_all_game_data();
+        let mut minions = started.clone();
+        minions["events"]["Events"]
+            .as_array_mut()
+            .unwrap()
+            .push(json!({ "EventID": 1, "EventName": "MinionsSpawning", "EventTime": 65.0 }));
+        let mut ended = minions.clone();
+        ended["events"]["Events"].as_array_mut().unwrap().push(
+            json!({ "EventID": 2, "EventName": "GameEnd", "EventTime": 900.0, "Result": "Win" }),
+        );
+
+        let server = MockGameServer::builder()
+            .timeline([minions.clone()])
+            .start()
+            .await
+            .unwrap();
+        let client = server.game_client();
+        let request_client = server.request_client();
+
+        let mut stream = pin!(client.event_stream(Duration::from_millis(10), &request_client));
+
+        assert_eq!(next(&mut stream).await.event_id(), 0);
+        assert_eq!(next(&mut stream).await.event_id(), 1);
+
+        // The game is closed before it ends, and the next one has fewer events
+        start_next_game(&server, &mut stream, started).await;
+        assert_eq!(next(&mut stream).await.event_id(), 0);
+
+        server.set_game_data(Some(ended));
+        assert_eq!(next(&mut stream).await.event_id(), 1);
+        assert_eq!(next(&mut stream).await.event_id(), 2);
+
+        // The next game has fewer events than the one that ended
+        start_next_game(&server, &mut stream, minions).await;
+        assert_eq!(next(&mut stream).await.event_id(), 0);
+        assert_eq!(next(&mut stream).await.event_id(), 1);
+    }
+}
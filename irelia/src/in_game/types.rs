@@ -444,6 +444,7 @@ impl ChampionStats {
 
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
+#[allow(clippy::struct_field_names)]
 pub struct Runes {
     keystone: Rune,
     primary_rune_tree: Rune,
@@ -746,6 +747,7 @@ impl SummonerSpell {
 
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
+#[allow(clippy::struct_field_names)]
 pub struct Item {
     can_use: bool,
     consumable: bool,
@@ -808,6 +810,14 @@ pub struct Events {
 }
 
 impl Events {
+    #[must_use]
+    pub fn events(&self) -> &[Event] {
+        &self.events
+    }
+    #[must_use]
+    pub fn into_events(self) -> Box<[Event]> {
+        self.events
+    }
     #[must_use]
     pub fn dragons_killed(&self) -> u8 {
         self.events.iter().fold(0, |acc, event| {
@@ -839,6 +849,7 @@ impl Events {
 
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 #[serde(rename_all = "PascalCase")]
+#[allow(clippy::struct_field_names)]
 pub struct Event {
     #[serde(rename = "EventID")]
     event_id: i64,
@@ -917,6 +928,7 @@ pub enum DragonType {
 
 /// This represents all the data concerning a Turret or Inhibitor
 #[derive(Debug, Clone, PartialEq)]
+#[allow(clippy::struct_field_names)]
 pub struct Structure {
     /// This is either `StructureType::Turret` or `StructureType::Barracks` aka inhibitor
     structure_type: StructureType,
@@ -1133,7 +1145,7 @@ impl<'de> serde::Deserialize<'de> for Structure {
 
         struct StructureVisitor;
 
-        impl<'a> Visitor<'a> for StructureVisitor {
+        impl Visitor<'_> for StructureVisitor {
             type Value = Structure;
 
             fn expecting(&self, formatter: &mut Formatter) -> std::fmt::Result {
@@ -1284,6 +1296,10 @@ impl Event {
     pub fn event_time(&self) -> Duration {
         self.event_time
     }
+    #[must_use]
+    pub fn event_details(&self) -> &EventDetails {
+        &self.event_details
+    }
 }
 
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
@@ -1501,6 +1517,8 @@ mod option_slice {
     use serde::ser::SerializeSeq;
     use serde::{Deserialize, Deserializer, Serializer};
 
+    // This has to be passed by ref to work with serde
+    #[allow(clippy::ref_option)]
     pub fn serialize<S: Serializer>(
         slice: &Option<Box<[String]>>,
         serializer: S,
@@ -1,4 +1,5 @@
 #![warn(clippy::pedantic)]
+#![allow(clippy::result_large_err)]
 #![forbid(unsafe_code)]
 
 //! Irelia is an async set of bindings to the LCU API
@@ -8,15 +9,28 @@
 //! - `rest`: Allows connections to the LCU `rest` API, providing basic get/post functionality
 //! - `ws`: Allows connections to the LCU websocket API, providing all functionality needed
 //! - `replay`: Allows connections to the `replay` API, also enables the in game API
+//! - `tracing`: Emits `tracing` spans and events for requests, connections, and subscriptions,
+//!   without it nothing is ever logged
+//! - `mock`: A local HTTPS and websocket server standing in for the LCU, for testing
+//!   code that uses the `rest` and `ws` APIs without a running client
+//! - `mock_game`: A local HTTPS server standing in for the game, for testing code that uses
+//!   the `in_game` and `replay` APIs, also enables the in game API
+//!
+//! Both the `rest` and `ws` APIs find the running client by default, this can be changed
+//! by passing any [`credentials::CredentialSource`] to them
 
 #[cfg(feature = "in_game")]
 pub mod in_game;
+#[cfg(any(feature = "mock", feature = "mock_game"))]
+pub mod mock;
 #[cfg(feature = "replay")]
 pub mod replay;
 #[cfg(feature = "rest")]
 pub mod rest;
 pub(crate) mod utils;
 #[cfg(any(feature = "ws", feature = "rest"))]
+pub use utils::credentials;
+#[cfg(any(feature = "ws", feature = "rest"))]
 pub use utils::process_info;
 #[cfg(feature = "ws")]
 pub mod ws;
@@ -36,6 +50,31 @@ pub enum Error {
     WebsocketError(tokio_tungstenite::tungstenite::Error),
     #[cfg(any(feature = "ws", feature = "rest"))]
     ProcessInfoError(process_info::Error),
+    /// The LCU responded with a status outside 200-299, the other fields are
+    /// taken from the standard error body it returns.
+    ///
+    /// If the body is not in the standard format, `error_code` is empty,
+    /// `message` holds the raw body, and `http_status` matches `status`
+    #[cfg(feature = "rest")]
+    Lcu {
+        status: hyper::StatusCode,
+        error_code: String,
+        message: String,
+        http_status: u16,
+    },
+    /// The in game or replay API responded with an error, either through a status
+    /// outside 200-299, or through an error body such as `RESOURCE_NOT_FOUND`,
+    /// which is returned until the loading screen ends.
+    ///
+    /// If the body is not in the standard format, `error_code` is empty,
+    /// `message` holds the raw body, and `http_status` matches `status`
+    #[cfg(feature = "in_game")]
+    Game {
+        status: hyper::StatusCode,
+        error_code: String,
+        message: String,
+        http_status: u16,
+    },
     SerdeJsonError(serde_json::Error),
     RmpSerdeEncode(rmp_serde::encode::Error),
     RmpSerdeDecode(rmp_serde::decode::Error),
@@ -87,6 +126,7 @@ impl From<rmp_serde::decode::Error> for Error {
     }
 }
 
+#[cfg(any(feature = "ws", feature = "rest"))]
 impl From<process_info::Error> for Error {
     fn from(value: process_info::Error) -> Self {
         Self::ProcessInfoError(value)
@@ -107,6 +147,20 @@ impl std::fmt::Display for Error {
             Error::WebsocketError(err) => err.to_string().into(),
             #[cfg(any(feature = "ws", feature = "rest"))]
             Error::ProcessInfoError(err) => err.reason().into(),
+            #[cfg(feature = "rest")]
+            Error::Lcu {
+                status,
+                error_code,
+                message,
+                ..
+            } => format!("{status} {error_code}: {message}").into(),
+            #[cfg(feature = "in_game")]
+            Error::Game {
+                status,
+                error_code,
+                message,
+                ..
+            } => format!("{status} {error_code}: {message}").into(),
             Error::RmpSerdeEncode(err) => err.to_string().into(),
             Error::RmpSerdeDecode(err) => err.to_string().into(),
         };
@@ -116,6 +170,20 @@ impl std::fmt::Display for Error {
 
 impl std::error::Error for Error {}
 
+impl Error {
+    #[must_use]
+    /// Returns the error code sent by the LCU, in game, or replay API, if there was one
+    pub fn error_code(&self) -> Option<&str> {
+        match self {
+            #[cfg(feature = "rest")]
+            Error::Lcu { error_code, .. } if !error_code.is_empty() => Some(error_code),
+            #[cfg(feature = "in_game")]
+            Error::Game { error_code, .. } if !error_code.is_empty() => Some(error_code),
+            _ => None,
+        }
+    }
+}
+
 impl serde::Serialize for Error {
     fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
     where
@@ -0,0 +1,222 @@
+//! Local stand ins for the LCU and the game, so code built on this crate can be tested
+//! without a running client
+//!
+//! The servers speak HTTPS on localhost, using a bundled self-signed certificate unless
+//! another one is given, and record every request that reaches them.
+//! [`MockServer`] stands in for the LCU and its WAMP websocket, and is enabled by the `mock` feature.
+//! The in game and replay APIs are mocked separately by [`game::MockGameServer`],
+//! which is enabled by the `mock_game` feature, and only needs `in_game` rather than `rest` and `ws`
+
+#[cfg(feature = "mock_game")]
+pub mod game;
+#[cfg(feature = "mock")]
+mod lcu;
+
+#[cfg(feature = "mock")]
+pub use lcu::{MockResponse, MockServer, MockServerBuilder, DEFAULT_PASSWORD};
+
+use std::convert::Infallible;
+use std::future::Future;
+use std::io;
+use std::sync::{Arc, Mutex, PoisonError};
+use std::time::Duration;
+
+use http_body_util::Full;
+use hyper::body::{Bytes, Incoming};
+use hyper::header::{HeaderValue, CONTENT_TYPE};
+use hyper::server::conn::http1;
+use hyper::service::service_fn;
+use hyper::{Method, Request, Response, StatusCode};
+use hyper_util::rt::TokioIo;
+use rustls::pki_types::CertificateDer;
+use rustls::{ClientConfig, RootCertStore, ServerConfig};
+use serde_json::{json, Value};
+use tokio::net::TcpListener;
+use tokio_rustls::TlsAcceptor;
+
+const CERT_CHAIN: &str = include_str!("mock/cert.pem");
+const PRIVATE_KEY: &str = include_str!("mock/key.pem");
+const ROOT_CERT: &str = include_str!("mock/ca.pem");
+
+/// How long to wait before accepting again, when accepting a connection fails
+const ACCEPT_BACKOFF: Duration = Duration::from_millis(100);
+
+/// A request that reached the server, after passing the auth check
+#[derive(Debug, Clone, PartialEq)]
+pub struct RecordedRequest {
+    pub method: Method,
+    /// The path, including the query string if there was one
+    pub path: String,
+    /// The body parsed as JSON, `None` if it was empty or not valid JSON
+    pub body: Option<Value>,
+}
+
+fn lock<T>(mutex: &Mutex<T>) -> std::sync::MutexGuard<'_, T> {
+    // Nothing here can be left half updated, so a poisoned lock is still usable
+    mutex.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+fn invalid(error: impl Into<Box<dyn std::error::Error + Send + Sync>>) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidInput, error)
+}
+
+fn read_certs(pem: &str) -> io::Result<Vec<CertificateDer<'static>>> {
+    rustls_pemfile::certs(&mut pem.as_bytes()).collect()
+}
+
+/// The PEM encoded certificate a mock server uses, by default the bundled one
+#[derive(Clone)]
+struct Certificate {
+    cert_chain: String,
+    private_key: String,
+    root_cert: String,
+}
+
+impl Certificate {
+    fn new(
+        cert_chain: impl Into<String>,
+        private_key: impl Into<String>,
+        root_cert: impl Into<String>,
+    ) -> Self {
+        Self {
+            cert_chain: cert_chain.into(),
+            private_key: private_key.into(),
+            root_cert: root_cert.into(),
+        }
+    }
+
+    /// Parses the certificate, returning the acceptor for the server,
+    /// and the roots the clients should trust
+    fn acceptor(&self) -> io::Result<(TlsAcceptor, Arc<RootCertStore>)> {
+        let root_cert = read_certs(&self.root_cert)?
+            .into_iter()
+            .next()
+            .ok_or_else(|| invalid("no root certificate was found"))?;
+        let mut roots = RootCertStore::empty();
+        roots.add(root_cert).map_err(invalid)?;
+        let cert_chain = read_certs(&self.cert_chain)?;
+        let private_key = rustls_pemfile::private_key(&mut self.private_key.as_bytes())?
+            .ok_or_else(|| invalid("no private key was found"))?;
+
+        let config = ServerConfig::builder()
+            .with_no_client_auth()
+            .with_single_cert(cert_chain, private_key)
+            .map_err(invalid)?;
+
+        Ok((TlsAcceptor::from(Arc::new(config)), Arc::new(roots)))
+    }
+}
+
+impl Default for Certificate {
+    fn default() -> Self {
+        Self::new(CERT_CHAIN, PRIVATE_KEY, ROOT_CERT)
+    }
+}
+
+fn client_config(roots: &Arc<RootCertStore>) -> ClientConfig {
+    ClientConfig::builder()
+        .with_root_certificates(roots.clone())
+        .with_no_client_auth()
+}
+
+/// Accepts connections until the task is aborted, passing every request to the handler
+async fn serve<H, F>(listener: TcpListener, acceptor: TlsAcceptor, handler: H)
+where
+    H: Fn(Request<Incoming>) -> F + Clone + Send + Sync + 'static,
+    F: Future<Output = Result<Response<Full<Bytes>>, Infallible>> + Send + 'static,
+{
+    loop {
+        let tcp_stream = match listener.accept().await {
+            Ok((tcp_stream, _)) => tcp_stream,
+            // Such as running out of file descriptors, which won't clear up straight away
+            Err(error) => {
+                #[cfg(feature = "tracing")]
+                tracing::warn!(%error, "the mock server failed to accept a connection");
+                #[cfg(not(feature = "tracing"))]
+                let _ = error;
+
+                tokio::time::sleep(ACCEPT_BACKOFF).await;
+                continue;
+            }
+        };
+
+        let acceptor = acceptor.clone();
+        let handler = handler.clone();
+
+        tokio::spawn(async move {
+            let Ok(tls_stream) = acceptor.accept(tcp_stream).await else {
+                return;
+            };
+
+            let service = service_fn(handler);
+
+            // Errors here are the client going away, which the test will see on its end
+            let _ = http1::Builder::new()
+                .serve_connection(TokioIo::new(tls_stream), service)
+                .with_upgrades()
+                .await;
+        });
+    }
+}
+
+fn json_response(status: StatusCode, body: &Value) -> Response<Full<Bytes>> {
+    let mut response = if body.is_null() {
+        Response::new(Full::default())
+    } else {
+        let mut response = Response::new(Full::new(Bytes::from(body.to_string())));
+        response
+            .headers_mut()
+            .insert(CONTENT_TYPE, HeaderValue::from_static("application/json"));
+        response
+    };
+
+    *response.status_mut() = status;
+    response
+}
+
+/// Replies in the same format the LCU and the game use for errors
+fn error_response(status: StatusCode, error_code: &str, message: &str) -> Response<Full<Bytes>> {
+    let body = json!({
+        "errorCode": error_code,
+        "httpStatus": status.as_u16(),
+        "implementationDetails": {},
+        "message": message,
+    });
+
+    json_response(status, &body)
+}
+
+#[cfg(test)]
+mod tests {
+    /// Parses as a certificate, but isn't one
+    const NOT_A_CERT: &str = "-----BEGIN CERTIFICATE-----\nAAAA\n-----END CERTIFICATE-----\n";
+
+    #[cfg(feature = "mock")]
+    #[tokio::test]
+    async fn test_invalid_root() {
+        use super::{MockServer, CERT_CHAIN, PRIVATE_KEY};
+
+        let error = MockServer::builder()
+            .certificate(CERT_CHAIN, PRIVATE_KEY, NOT_A_CERT)
+            .start()
+            .await
+            .err()
+            .unwrap();
+        assert_eq!(error.kind(), std::io::ErrorKind::InvalidInput);
+    }
+
+    #[cfg(feature = "mock_game")]
+    #[tokio::test]
+    async fn test_invalid_game_root() {
+        use super::game::MockGameServer;
+        use super::{CERT_CHAIN, PRIVATE_KEY};
+
+        let error = MockGameServer::builder()
+            .certificate(CERT_CHAIN, PRIVATE_KEY, NOT_A_CERT)
+            .start()
+            .await
+            .err()
+            .unwrap();
+        assert_eq!(error.kind(), std::io::ErrorKind::InvalidInput);
+    }
+}
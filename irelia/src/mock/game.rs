@@ -0,0 +1,463 @@
+//! A local stand in for the in game and replay APIs, served from fixtures
+//!
+//! Every `/liveclientdata/*` endpoint is derived from the current `allgamedata` snapshot,
+//! which steps through a scripted timeline, the same way the real endpoints are views of it.
+//! `/replay/*` endpoints are served from fixtures, which `POST` requests update, the same
+//! way the game applies them. Any path can also be given a fixture of its own,
+//! which is served instead of the derived data
+//!
+//! Replies are `MsgPack` when the request accepts it, as the replay API does, and JSON otherwise
+
+use std::collections::{HashMap, VecDeque};
+use std::convert::Infallible;
+use std::io;
+use std::net::{Ipv4Addr, SocketAddr};
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+
+use http_body_util::{BodyExt, Full};
+use hyper::body::{Bytes, Incoming};
+use hyper::header::{HeaderName, HeaderValue, ACCEPT, CONTENT_TYPE};
+use hyper::{Method, Request, Response, StatusCode};
+use rustls::{ClientConfig, RootCertStore};
+use serde_json::Value;
+use tokio::net::TcpListener;
+use tokio::task::JoinHandle;
+
+use super::{client_config, error_response, json_response, lock, serve, Certificate};
+use super::{invalid, RecordedRequest};
+use crate::in_game::GameClient;
+#[cfg(feature = "replay")]
+use crate::replay::ReplayClient;
+use crate::RequestClient;
+
+const ALL_GAME_DATA: &str = include_str!("allgamedata.json");
+
+/// The error code the game sends with most errors, including while the game is loading
+const GAME_ERROR_CODE: &str = "RESOURCE_NOT_FOUND";
+
+const MSGPACK: &str = "application/x-msgpack";
+
+#[must_use]
+/// A small but complete `allgamedata` snapshot, with one player on each team,
+/// to use as the start of a timeline
+///
+/// # Panics
+/// If the bundled snapshot is not valid JSON, which is checked by the tests
+pub fn sample_all_game_data() -> Value {
+    serde_json::from_str(ALL_GAME_DATA).expect("the bundled snapshot should be valid JSON")
+}
+
+struct Timeline {
+    current: Option<Value>,
+    upcoming: VecDeque<Value>,
+}
+
+struct State {
+    fixtures: Mutex<HashMap<String, Value>>,
+    timeline: Mutex<Timeline>,
+    requests: Mutex<Vec<RecordedRequest>>,
+}
+
+/// Configures a `MockGameServer` before it is started
+pub struct MockGameServerBuilder {
+    certificate: Certificate,
+    fixtures: HashMap<String, Value>,
+    timeline: VecDeque<Value>,
+}
+
+impl MockGameServerBuilder {
+    #[must_use]
+    /// Replaces the bundled certificate, see `MockServerBuilder::certificate`
+    pub fn certificate(
+        mut self,
+        cert_chain: impl Into<String>,
+        private_key: impl Into<String>,
+        root_cert: impl Into<String>,
+    ) -> Self {
+        self.certificate = Certificate::new(cert_chain, private_key, root_cert);
+        self
+    }
+
+    #[must_use]
+    /// Serves the value on the path, such as `/replay/playback`,
+    /// the query string is only matched if the path includes one
+    pub fn fixture(mut self, path: impl Into<String>, value: Value) -> Self {
+        self.fixtures.insert(path.into(), value);
+        self
+    }
+
+    /// Same as `fixture`, but reads the value from a file, which is decoded as `MsgPack`
+    /// if the extension is `msgpack` or `mp`, and as JSON otherwise
+    ///
+    /// # Errors
+    /// This will return an error if the file cannot be read or decoded
+    pub fn fixture_file(self, path: impl Into<String>, file: impl AsRef<Path>) -> io::Result<Self> {
+        let file = file.as_ref();
+        let bytes = std::fs::read(file)?;
+
+        let is_msgpack = file
+            .extension()
+            .is_some_and(|extension| extension == "msgpack" || extension == "mp");
+
+        let value = if is_msgpack {
+            rmp_serde::from_slice(&bytes).map_err(invalid)?
+        } else {
+            serde_json::from_slice(&bytes).map_err(invalid)?
+        };
+
+        Ok(self.fixture(path, value))
+    }
+
+    #[must_use]
+    /// Appends `allgamedata` snapshots to the timeline, the first one is served as soon
+    /// as the server starts, and `MockGameServer::advance` moves to the next
+    ///
+    /// Until there is a snapshot, every derived endpoint answers the same way as
+    /// the game does while loading
+    pub fn timeline(mut self, snapshots: impl IntoIterator<Item = Value>) -> Self {
+        self.timeline.extend(snapshots);
+        self
+    }
+
+    /// Binds a random port on localhost, and starts serving on the current tokio runtime
+    ///
+    /// # Errors
+    /// This will return an error if the certificate cannot be parsed,
+    /// or if the port cannot be bound
+    pub async fn start(mut self) -> io::Result<MockGameServer> {
+        let (acceptor, roots) = self.certificate.acceptor()?;
+
+        let listener = TcpListener::bind((Ipv4Addr::LOCALHOST, 0)).await?;
+        let addr = listener.local_addr()?;
+
+        let timeline = Timeline {
+            current: self.timeline.pop_front(),
+            upcoming: self.timeline,
+        };
+
+        let state = Arc::new(State {
+            fixtures: Mutex::new(self.fixtures),
+            timeline: Mutex::new(timeline),
+            requests: Mutex::default(),
+        });
+
+        let handler_state = state.clone();
+        let handle = tokio::spawn(serve(listener, acceptor, move |request| {
+            handle(request, handler_state.clone())
+        }));
+
+        Ok(MockGameServer {
+            addr,
+            roots,
+            state,
+            handle,
+        })
+    }
+}
+
+/// A running mock of the in game and replay APIs, which stops accepting connections when dropped
+pub struct MockGameServer {
+    addr: SocketAddr,
+    roots: Arc<RootCertStore>,
+    state: Arc<State>,
+    handle: JoinHandle<()>,
+}
+
+impl MockGameServer {
+    #[must_use]
+    /// Starts configuring a server with the bundled certificate, no fixtures, and an empty timeline
+    pub fn builder() -> MockGameServerBuilder {
+        MockGameServerBuilder {
+            certificate: Certificate::default(),
+            fixtures: HashMap::new(),
+            timeline: VecDeque::new(),
+        }
+    }
+
+    #[must_use]
+    pub fn addr(&self) -> SocketAddr {
+        self.addr
+    }
+
+    #[must_use]
+    /// A TLS config which only trusts the root certificate of the server
+    pub fn client_config(&self) -> ClientConfig {
+        client_config(&self.roots)
+    }
+
+    #[must_use]
+    /// A request client which trusts the server
+    pub fn request_client(&self) -> RequestClient {
+        RequestClient::with_tls_config(self.client_config())
+    }
+
+    #[must_use]
+    /// A `GameClient` pointing at the server
+    pub fn game_client(&self) -> GameClient {
+        GameClient::with_url(self.addr.to_string())
+    }
+
+    #[cfg(feature = "replay")]
+    #[must_use]
+    /// A `ReplayClient` pointing at the server
+    pub fn replay_client(&self) -> ReplayClient {
+        ReplayClient::with_url(self.addr.to_string())
+    }
+
+    /// Adds or replaces a fixture, this takes effect for the next request
+    pub fn set_fixture(&self, path: impl Into<String>, value: Value) {
+        lock(&self.state.fixtures).insert(path.into(), value);
+    }
+
+    #[must_use]
+    /// Removes a fixture, returning the value it had
+    pub fn remove_fixture(&self, path: &str) -> Option<Value> {
+        lock(&self.state.fixtures).remove(path)
+    }
+
+    #[must_use]
+    /// The value currently served for the fixture, including changes made by `POST` requests
+    pub fn fixture(&self, path: &str) -> Option<Value> {
+        lock(&self.state.fixtures).get(path).cloned()
+    }
+
+    /// Appends snapshots to the end of the timeline
+    pub fn extend_timeline(&self, snapshots: impl IntoIterator<Item = Value>) {
+        let mut timeline = lock(&self.state.timeline);
+
+        timeline.upcoming.extend(snapshots);
+
+        if timeline.current.is_none() {
+            timeline.current = timeline.upcoming.pop_front();
+        }
+    }
+
+    #[must_use]
+    /// Moves to the next snapshot, returning false if the timeline has ended,
+    /// in which case the last snapshot is still served
+    pub fn advance(&self) -> bool {
+        let mut timeline = lock(&self.state.timeline);
+
+        match timeline.upcoming.pop_front() {
+            Some(next) => {
+                timeline.current = Some(next);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Replaces the current snapshot, leaving the rest of the timeline as is,
+    /// `None` makes the server answer as if the game was loading
+    pub fn set_game_data(&self, snapshot: Option<Value>) {
+        lock(&self.state.timeline).current = snapshot;
+    }
+
+    #[must_use]
+    /// The snapshot currently being served
+    pub fn game_data(&self) -> Option<Value> {
+        lock(&self.state.timeline).current.clone()
+    }
+
+    #[must_use]
+    /// Every request received so far, in the order they arrived
+    pub fn requests(&self) -> Vec<RecordedRequest> {
+        lock(&self.state.requests).clone()
+    }
+}
+
+impl Drop for MockGameServer {
+    fn drop(&mut self) {
+        self.handle.abort();
+    }
+}
+
+async fn handle(
+    request: Request<Incoming>,
+    state: Arc<State>,
+) -> Result<Response<Full<Bytes>>, Infallible> {
+    let accepts_msgpack = is_msgpack(&request, ACCEPT);
+    let sent_msgpack = is_msgpack(&request, CONTENT_TYPE);
+
+    let method = request.method().clone();
+    let path = request
+        .uri()
+        .path_and_query()
+        .map_or_else(|| request.uri().path().to_string(), ToString::to_string);
+    let bare_path = request.uri().path().to_string();
+    let query = request.uri().query().map(parse_query).unwrap_or_default();
+
+    let body = match request.into_body().collect().await {
+        Ok(body) if sent_msgpack => rmp_serde::from_slice(&body.to_bytes()).ok(),
+        Ok(body) => serde_json::from_slice(&body.to_bytes()).ok(),
+        Err(_) => None,
+    };
+
+    let response = if method == Method::POST {
+        body.clone()
+            .and_then(|body| apply_post(&state, &bare_path, body))
+    } else {
+        let fixture = {
+            let fixtures = lock(&state.fixtures);
+            fixtures
+                .get(&path)
+                .or_else(|| fixtures.get(&bare_path))
+                .cloned()
+        };
+
+        fixture.or_else(|| {
+            let endpoint = bare_path.strip_prefix("/liveclientdata/")?;
+            let timeline = lock(&state.timeline);
+            derive(endpoint, &query, timeline.current.as_ref()?)
+        })
+    };
+
+    let is_head = method == Method::HEAD;
+    lock(&state.requests).push(RecordedRequest { method, path, body });
+
+    Ok(match response {
+        // The game answers invalid methods with an empty 200, which is what `GameClient::head` relies on
+        Some(_) if is_head => json_response(StatusCode::OK, &Value::Null),
+        Some(value) if accepts_msgpack => msgpack_response(&value),
+        Some(value) => json_response(StatusCode::OK, &value),
+        None => error_response(StatusCode::NOT_FOUND, GAME_ERROR_CODE, "Invalid URI format"),
+    })
+}
+
+fn is_msgpack(request: &Request<Incoming>, header: HeaderName) -> bool {
+    request
+        .headers()
+        .get(header)
+        .is_some_and(|value| value.as_bytes().starts_with(MSGPACK.as_bytes()))
+}
+
+fn msgpack_response(value: &Value) -> Response<Full<Bytes>> {
+    let Ok(body) = rmp_serde::to_vec_named(value) else {
+        return error_response(
+            StatusCode::INTERNAL_SERVER_ERROR,
+            GAME_ERROR_CODE,
+            "The fixture could not be encoded as MsgPack",
+        );
+    };
+
+    let mut response = Response::new(Full::new(Bytes::from(body)));
+    response
+        .headers_mut()
+        .insert(CONTENT_TYPE, HeaderValue::from_static(MSGPACK));
+    response
+}
+
+/// Applies the fields of the body to the fixture, the same way the replay API
+/// only changes the values that were sent, and returns the new value
+fn apply_post(state: &State, path: &str, body: Value) -> Option<Value> {
+    let mut fixtures = lock(&state.fixtures);
+    let fixture = fixtures.get_mut(path)?;
+
+    match (fixture.as_object_mut(), body) {
+        (Some(fields), Value::Object(changes)) => fields.extend(changes),
+        (_, body) => *fixture = body,
+    }
+
+    Some(fixture.clone())
+}
+
+/// Builds the reply for a `/liveclientdata/` endpoint out of the `allgamedata` snapshot
+fn derive(endpoint: &str, query: &HashMap<String, String>, data: &Value) -> Option<Value> {
+    let player = || {
+        let riot_id = query.get("riotId")?;
+
+        data.get("allPlayers")?
+            .as_array()?
+            .iter()
+            .find(|player| player.get("riotId").and_then(Value::as_str) == Some(riot_id))
+    };
+
+    let active_player = || data.get("activePlayer");
+
+    let value = match endpoint {
+        "allgamedata" => data.clone(),
+        "activeplayer" => active_player()?.clone(),
+        "activeplayername" => active_player()?.get("riotId")?.clone(),
+        "activeplayerabilities" => active_player()?.get("abilities")?.clone(),
+        "activeplayerrunes" => active_player()?.get("fullRunes")?.clone(),
+        "playerlist" => {
+            let team = query.get("teamID").map(String::as_str);
+            let players = data.get("allPlayers")?.as_array()?;
+
+            players
+                .iter()
+                .filter(|player| {
+                    team.is_none_or(|team| {
+                        team == "ALL" || player.get("team").and_then(Value::as_str) == Some(team)
+                    })
+                })
+                .cloned()
+                .collect()
+        }
+        "playerscores" => player()?.get("scores")?.clone(),
+        "playersummonerspells" => player()?.get("summonerSpells")?.clone(),
+        "playermainrunes" => player()?.get("runes")?.clone(),
+        "playeritems" => player()?.get("items")?.clone(),
+        "eventdata" => {
+            // The ID is the first event the caller has not seen yet
+            let first = query
+                .get("eventID")
+                .and_then(|id| id.parse::<i64>().ok())
+                .unwrap_or(0);
+            let events = data.get("events")?.get("Events")?.as_array()?;
+
+            let events = events
+                .iter()
+                .filter(|event| {
+                    event
+                        .get("EventID")
+                        .and_then(Value::as_i64)
+                        .is_some_and(|id| id >= first)
+                })
+                .cloned()
+                .collect();
+
+            let mut reply = serde_json::Map::new();
+            reply.insert("Events".to_string(), Value::Array(events));
+            Value::Object(reply)
+        }
+        "gamestats" => data.get("gameData")?.clone(),
+        _ => return None,
+    };
+
+    Some(value)
+}
+
+fn parse_query(query: &str) -> HashMap<String, String> {
+    query
+        .split('&')
+        .filter_map(|pair| pair.split_once('='))
+        .map(|(key, value)| (percent_decode(key), percent_decode(value)))
+        .collect()
+}
+
+/// Decodes `%XX` escapes, leaving anything malformed as it was
+fn percent_decode(value: &str) -> String {
+    let bytes = value.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+
+    while i < bytes.len() {
+        let escaped = (bytes[i] == b'%')
+            .then(|| bytes.get(i + 1..i + 3))
+            .flatten()
+            .and_then(|hex| std::str::from_utf8(hex).ok())
+            .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+
+        if let Some(byte) = escaped {
+            decoded.push(byte);
+            i += 3;
+        } else {
+            decoded.push(bytes[i]);
+            i += 1;
+        }
+    }
+
+    String::from_utf8_lossy(&decoded).into_owned()
+}
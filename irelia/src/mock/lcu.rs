@@ -0,0 +1,539 @@
+//! The mock of the LCU, re-exported from the parent module
+
+use std::collections::{HashMap, HashSet};
+use std::convert::Infallible;
+use std::io;
+use std::net::{Ipv4Addr, SocketAddr};
+use std::pin::pin;
+use std::sync::{Arc, Mutex};
+
+use futures_util::future::{select, Either};
+use futures_util::{SinkExt, StreamExt};
+use http_body_util::{BodyExt, Full};
+use hyper::body::{Bytes, Incoming};
+use hyper::header::{
+    HeaderValue, AUTHORIZATION, CONNECTION, SEC_WEBSOCKET_ACCEPT, SEC_WEBSOCKET_KEY, UPGRADE,
+};
+use hyper::{Method, Request, Response, StatusCode};
+use hyper_util::rt::TokioIo;
+use rustls::{ClientConfig, RootCertStore};
+use serde_json::{json, Value};
+use tokio::net::TcpListener;
+use tokio::sync::broadcast;
+use tokio::task::JoinHandle;
+use tokio_tungstenite::WebSocketStream;
+use tungstenite::handshake::derive_accept_key;
+use tungstenite::protocol::frame::coding::CloseCode;
+use tungstenite::protocol::{CloseFrame, Role};
+use tungstenite::Message;
+
+use super::RecordedRequest;
+use super::{client_config, error_response, json_response, lock, serve, Certificate};
+use crate::rest::LcuClient;
+use crate::utils::credentials::Fixed;
+use crate::utils::process_info::auth_header;
+use crate::ws::types::{EventKind, EventType, RequestType};
+use crate::RequestClient;
+
+/// The error code the LCU sends with most errors
+const LCU_ERROR_CODE: &str = "RPC_ERROR";
+
+/// The password used when none is given to the builder
+pub const DEFAULT_PASSWORD: &str = "irelia-mock";
+
+/// A scripted reply to a request
+#[derive(Debug, Clone, PartialEq)]
+pub struct MockResponse {
+    pub status: StatusCode,
+    /// `Value::Null` is sent as an empty body, the same way the LCU answers with a 204
+    pub body: Value,
+}
+
+type Routes = HashMap<(Method, String), MockResponse>;
+
+#[derive(Default)]
+struct State {
+    routes: Mutex<Routes>,
+    requests: Mutex<Vec<RecordedRequest>>,
+    /// How many open connections are subscribed to each event name
+    subscriptions: Mutex<HashMap<String, usize>>,
+}
+
+struct Shared {
+    auth_header: String,
+    state: State,
+    events: broadcast::Sender<Broadcast>,
+}
+
+/// Sent from the server handle to every open websocket connection
+#[derive(Debug, Clone)]
+enum Broadcast {
+    /// The event name, and the full frame to send to its subscribers
+    Event(String, String),
+    /// Closes the connection
+    Disconnect,
+}
+
+/// Configures a `MockServer` before it is started
+pub struct MockServerBuilder {
+    password: String,
+    certificate: Certificate,
+    routes: Routes,
+}
+
+impl MockServerBuilder {
+    #[must_use]
+    /// Sets the password the server expects, in place of `DEFAULT_PASSWORD`
+    pub fn password(mut self, password: impl Into<String>) -> Self {
+        self.password = password.into();
+        self
+    }
+
+    #[must_use]
+    /// Replaces the bundled certificate, all three arguments are PEM encoded
+    ///
+    /// The chain must be valid for `127.0.0.1`, and `root_cert` is what the clients
+    /// returned by the server are told to trust
+    pub fn certificate(
+        mut self,
+        cert_chain: impl Into<String>,
+        private_key: impl Into<String>,
+        root_cert: impl Into<String>,
+    ) -> Self {
+        self.certificate = Certificate::new(cert_chain, private_key, root_cert);
+        self
+    }
+
+    #[must_use]
+    /// Adds a scripted route, see `MockServer::route`
+    pub fn route(
+        mut self,
+        method: Method,
+        path: impl Into<String>,
+        status: StatusCode,
+        body: Value,
+    ) -> Self {
+        self.routes
+            .insert((method, path.into()), MockResponse { status, body });
+        self
+    }
+
+    /// Binds a random port on localhost, and starts serving on the current tokio runtime
+    ///
+    /// # Errors
+    /// This will return an error if the certificate cannot be parsed,
+    /// or if the port cannot be bound
+    pub async fn start(self) -> io::Result<MockServer> {
+        let (acceptor, roots) = self.certificate.acceptor()?;
+
+        let listener = TcpListener::bind((Ipv4Addr::LOCALHOST, 0)).await?;
+        let addr = listener.local_addr()?;
+
+        let (events, _) = broadcast::channel(64);
+        let shared = Arc::new(Shared {
+            auth_header: auth_header(&self.password),
+            state: State {
+                routes: Mutex::new(self.routes),
+                ..State::default()
+            },
+            events,
+        });
+
+        let handler_shared = shared.clone();
+        let handle = tokio::spawn(serve(listener, acceptor, move |request| {
+            handle(request, handler_shared.clone())
+        }));
+
+        Ok(MockServer {
+            addr,
+            password: self.password,
+            roots,
+            shared,
+            handle,
+        })
+    }
+}
+
+/// A running mock of the LCU, which stops accepting connections when dropped
+///
+/// Every request is checked against a Basic auth password, responses are scripted per route,
+/// and events are published by the test over the WAMP websocket
+///
+/// ```no_run
+/// use hyper::{Method, StatusCode};
+/// use irelia::mock::MockServer;
+/// use serde_json::json;
+///
+/// # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+/// let server = MockServer::builder()
+///     .route(
+///         Method::GET,
+///         "/lol-summoner/v1/current-summoner",
+///         StatusCode::OK,
+///         json!({ "gameName": "Irelia" }),
+///     )
+///     .start()
+///     .await?;
+///
+/// let lcu_client = server.lcu_client();
+/// let request_client = server.request_client();
+///
+/// let summoner: Option<serde_json::Value> = lcu_client
+///     .get("/lol-summoner/v1/current-summoner", &request_client)
+///     .await?;
+/// # Ok(())
+/// # }
+/// ```
+pub struct MockServer {
+    addr: SocketAddr,
+    password: String,
+    roots: Arc<RootCertStore>,
+    shared: Arc<Shared>,
+    handle: JoinHandle<()>,
+}
+
+impl MockServer {
+    #[must_use]
+    /// Starts configuring a server with the bundled certificate and `DEFAULT_PASSWORD`
+    pub fn builder() -> MockServerBuilder {
+        MockServerBuilder {
+            password: DEFAULT_PASSWORD.to_string(),
+            certificate: Certificate::default(),
+            routes: Routes::new(),
+        }
+    }
+
+    #[must_use]
+    pub fn addr(&self) -> SocketAddr {
+        self.addr
+    }
+
+    #[must_use]
+    pub fn password(&self) -> &str {
+        &self.password
+    }
+
+    #[must_use]
+    /// The formatted Basic auth header the server expects
+    pub fn auth_header(&self) -> &str {
+        &self.shared.auth_header
+    }
+
+    #[must_use]
+    /// A credential source pointing at the server, for the websocket clients
+    pub fn credentials(&self) -> Fixed {
+        Fixed::new(self.addr, self.shared.auth_header.clone())
+    }
+
+    #[must_use]
+    /// A TLS config which only trusts the root certificate of the server
+    ///
+    /// # Panics
+    /// If the root certificate is not a valid trust anchor
+    pub fn client_config(&self) -> ClientConfig {
+        client_config(&self.roots)
+    }
+
+    #[must_use]
+    /// A request client which trusts the server
+    pub fn request_client(&self) -> RequestClient {
+        RequestClient::with_tls_config(self.client_config())
+    }
+
+    #[must_use]
+    /// An `LcuClient` pointing at the server
+    pub fn lcu_client(&self) -> LcuClient {
+        LcuClient::new_with_credentials(self.addr, self.shared.auth_header.clone())
+    }
+
+    /// Adds or replaces a route, this takes effect for the next request
+    ///
+    /// Paths are matched including the query string first, then without it,
+    /// any request without a route is answered with a 404 in the format the LCU uses
+    pub fn route(&self, method: Method, path: impl Into<String>, status: StatusCode, body: Value) {
+        lock(&self.shared.state.routes)
+            .insert((method, path.into()), MockResponse { status, body });
+    }
+
+    #[must_use]
+    /// Removes a route, returning the response it had
+    pub fn remove_route(&self, method: &Method, path: &str) -> Option<MockResponse> {
+        lock(&self.shared.state.routes).remove(&(method.clone(), path.to_string()))
+    }
+
+    #[must_use]
+    /// Every request received so far, in the order they arrived
+    pub fn requests(&self) -> Vec<RecordedRequest> {
+        lock(&self.shared.state.requests).clone()
+    }
+
+    #[must_use]
+    /// Whether any open connection is subscribed to the event, such as `OnJsonApiEvent`
+    pub fn subscribed(&self, event_name: &str) -> bool {
+        lock(&self.shared.state.subscriptions)
+            .get(event_name)
+            .is_some_and(|count| *count != 0)
+    }
+
+    /// Publishes a `JsonApiEvent`, to connections subscribed to either every
+    /// `OnJsonApiEvent`, or to the callback for this uri
+    pub fn publish(&self, uri: &str, event_type: &EventType, data: Value) {
+        let mut payload = json!({
+            "eventType": event_type.as_str(),
+            "uri": uri,
+        });
+        payload["data"] = data;
+
+        let callback = EventKind::JsonApiEventCallback(uri.to_string()).to_string();
+
+        self.emit(&callback, payload.clone());
+        self.emit(&EventKind::JsonApiEvent.to_string(), payload);
+    }
+
+    /// Sends `[8, event_name, payload]` to every connection subscribed to the event name
+    pub fn emit(&self, event_name: &str, payload: Value) {
+        let frame = Value::Array(vec![
+            RequestType::Event.code().into(),
+            event_name.into(),
+            payload,
+        ])
+        .to_string();
+
+        // Fails only if nothing is connected, in which case there is nobody to tell
+        let _ = self
+            .shared
+            .events
+            .send(Broadcast::Event(event_name.to_string(), frame));
+    }
+
+    /// Closes every open websocket connection, the same way the LCU does when it restarts
+    ///
+    /// New connections are still accepted, and their subscriptions start out empty
+    pub fn disconnect(&self) {
+        // Fails only if nothing is connected, in which case there is nothing to close
+        let _ = self.shared.events.send(Broadcast::Disconnect);
+    }
+}
+
+impl Drop for MockServer {
+    fn drop(&mut self) {
+        self.handle.abort();
+    }
+}
+
+async fn handle(
+    request: Request<Incoming>,
+    shared: Arc<Shared>,
+) -> Result<Response<Full<Bytes>>, Infallible> {
+    let authorized = request
+        .headers()
+        .get(AUTHORIZATION)
+        .is_some_and(|auth| auth.as_bytes() == shared.auth_header.as_bytes());
+
+    if !authorized {
+        return Ok(error_response(
+            StatusCode::UNAUTHORIZED,
+            LCU_ERROR_CODE,
+            "Unauthorized",
+        ));
+    }
+
+    if let Some(key) = websocket_key(&request) {
+        let accept = derive_accept_key(key.as_bytes());
+
+        tokio::spawn(async move {
+            if let Ok(upgraded) = hyper::upgrade::on(request).await {
+                let stream =
+                    WebSocketStream::from_raw_socket(TokioIo::new(upgraded), Role::Server, None)
+                        .await;
+
+                websocket(stream, &shared).await;
+            }
+        });
+
+        let mut response = Response::new(Full::default());
+        *response.status_mut() = StatusCode::SWITCHING_PROTOCOLS;
+        let headers = response.headers_mut();
+        headers.insert(CONNECTION, HeaderValue::from_static("Upgrade"));
+        headers.insert(UPGRADE, HeaderValue::from_static("websocket"));
+        headers.insert(
+            SEC_WEBSOCKET_ACCEPT,
+            HeaderValue::from_str(&accept).expect("the accept key is base64"),
+        );
+
+        return Ok(response);
+    }
+
+    let method = request.method().clone();
+    let path = request
+        .uri()
+        .path_and_query()
+        .map_or_else(|| request.uri().path().to_string(), ToString::to_string);
+    let bare_path = request.uri().path().to_string();
+
+    let body = match request.into_body().collect().await {
+        Ok(body) => serde_json::from_slice(&body.to_bytes()).ok(),
+        Err(_) => None,
+    };
+
+    let response = {
+        let routes = lock(&shared.state.routes);
+
+        routes
+            .get(&(method.clone(), path.clone()))
+            .or_else(|| routes.get(&(method.clone(), bare_path)))
+            .cloned()
+    };
+
+    lock(&shared.state.requests).push(RecordedRequest { method, path, body });
+
+    Ok(match response {
+        Some(response) => json_response(response.status, &response.body),
+        None => error_response(StatusCode::NOT_FOUND, LCU_ERROR_CODE, "Invalid URI format"),
+    })
+}
+
+fn websocket_key(request: &Request<Incoming>) -> Option<String> {
+    let headers = request.headers();
+
+    let is_upgrade = headers
+        .get(UPGRADE)
+        .and_then(|upgrade| upgrade.to_str().ok())
+        .is_some_and(|upgrade| upgrade.eq_ignore_ascii_case("websocket"));
+
+    if !is_upgrade {
+        return None;
+    }
+
+    headers
+        .get(SEC_WEBSOCKET_KEY)
+        .and_then(|key| key.to_str().ok())
+        .map(ToString::to_string)
+}
+
+async fn websocket(
+    mut stream: WebSocketStream<TokioIo<hyper::upgrade::Upgraded>>,
+    shared: &Shared,
+) {
+    let mut events = shared.events.subscribe();
+    let mut subscribed = HashSet::new();
+    let mut disconnect = false;
+
+    loop {
+        let next = {
+            let frame = pin!(stream.next());
+            let event = pin!(events.recv());
+
+            match select(frame, event).await {
+                Either::Left((frame, _)) => Either::Left(frame),
+                Either::Right((event, _)) => Either::Right(event),
+            }
+        };
+
+        let reply = match next {
+            Either::Left(Some(Ok(Message::Text(text)))) => {
+                handle_frame(&text, shared, &mut subscribed)
+            }
+            Either::Left(Some(Ok(Message::Close(_)) | Err(_)) | None)
+            | Either::Right(Err(broadcast::error::RecvError::Closed)) => break,
+            Either::Right(Ok(Broadcast::Event(event_name, frame))) => {
+                subscribed.contains(&event_name).then_some(frame)
+            }
+            Either::Right(Ok(Broadcast::Disconnect)) => {
+                disconnect = true;
+                break;
+            }
+            // Pings are answered by tungstenite, and falling behind on events is something
+            // a test can't act on either way
+            Either::Left(Some(Ok(_)))
+            | Either::Right(Err(broadcast::error::RecvError::Lagged(_))) => None,
+        };
+
+        if let Some(reply) = reply {
+            if stream.send(Message::Text(reply)).await.is_err() {
+                break;
+            }
+        }
+    }
+
+    {
+        let mut subscriptions = lock(&shared.state.subscriptions);
+        for event_name in subscribed {
+            if let Some(count) = subscriptions.get_mut(&event_name) {
+                *count -= 1;
+            }
+        }
+    }
+
+    // The subscriptions are dropped first, so once the client sees the close frame,
+    // `MockServer::subscribed` only counts the connections that are left
+    if disconnect {
+        let frame = CloseFrame {
+            code: CloseCode::Away,
+            reason: "Shutting down".into(),
+        };
+        let _ = stream.close(Some(frame)).await;
+    }
+}
+
+/// Handles a single WAMP frame from the client, returning the reply if there is one
+fn handle_frame(text: &str, shared: &Shared, subscribed: &mut HashSet<String>) -> Option<String> {
+    let frame: Value = serde_json::from_str(text).ok()?;
+    let parts = frame.as_array()?;
+    let code = parts.first().and_then(Value::as_u64)?;
+
+    if code == RequestType::Subscribe.code() || code == RequestType::Unsubscribe.code() {
+        let event_name = parts.get(1).and_then(Value::as_str)?.to_string();
+        let mut subscriptions = lock(&shared.state.subscriptions);
+        let count = subscriptions.entry(event_name.clone()).or_default();
+
+        if code == RequestType::Subscribe.code() {
+            if subscribed.insert(event_name) {
+                *count += 1;
+            }
+        } else if subscribed.remove(&event_name) {
+            *count -= 1;
+        }
+
+        return None;
+    }
+
+    if code != RequestType::Call.code() {
+        return None;
+    }
+
+    let id = parts.get(1)?;
+    let uri = parts.get(2).and_then(Value::as_str)?;
+
+    // Calls are either `METHOD /path`, or just a path which is treated as a GET
+    let (method, path) = match uri.split_once(' ') {
+        Some((method, path)) => (Method::from_bytes(method.as_bytes()).ok()?, path),
+        None => (Method::GET, uri),
+    };
+
+    let response = lock(&shared.state.routes)
+        .get(&(method, path.to_string()))
+        .cloned();
+
+    let reply = match response {
+        Some(response) if response.status.is_success() => {
+            json!([RequestType::CallResult.code(), id, response.body])
+        }
+        Some(response) => json!([
+            RequestType::CallError.code(),
+            id,
+            "error",
+            response.status.as_u16(),
+            response.body
+        ]),
+        None => json!([
+            RequestType::CallError.code(),
+            id,
+            "error",
+            StatusCode::NOT_FOUND.as_u16(),
+            "Invalid URI format"
+        ]),
+    };
+
+    Some(reply.to_string())
+}
@@ -11,22 +11,40 @@ pub mod types;
 /// A number of endpoints are also shared
 /// Hence why the replay API enables the `in_game` feature
 pub use super::in_game::URL;
+use crate::in_game::parse_response;
 use crate::replay::types::{Playback, RecordingState, Render, Sequence};
 use crate::utils::requests::SerializeFormat;
 use crate::{Error, RequestClient};
 use serde::de::DeserializeOwned;
 use serde::Serialize;
-use std::borrow::Borrow;
+use std::borrow::{Borrow, Cow};
 use std::collections::HashMap;
 
+/// Struct that represents a connection to the replay API
+///
+/// This points at `URL` unless another address is given, such as a mock server
 #[allow(clippy::module_name_repetitions)]
-#[derive(Default)]
-pub struct ReplayClient;
+#[derive(Debug, Clone)]
+pub struct ReplayClient {
+    url: Cow<'static, str>,
+}
 
 impl ReplayClient {
     #[must_use]
     pub fn new() -> Self {
-        Self
+        Self::with_url(URL)
+    }
+
+    #[must_use]
+    /// Points the client at another address, in the same `host:port` format as `URL`
+    pub fn with_url(url: impl Into<Cow<'static, str>>) -> Self {
+        Self { url: url.into() }
+    }
+
+    #[must_use]
+    /// Returns the url, which is `URL` by default
+    pub fn url(&self) -> &str {
+        &self.url
     }
 
     /// Information about the game client process.
@@ -170,14 +188,60 @@ impl ReplayClient {
     where
         R: DeserializeOwned,
     {
-        use hyper::body::Buf;
-
         let endpoint = format!("/replay/{endpoint}");
 
-        let buffer = request_client
-            .request_template(URL, &endpoint, method, body, None, SerializeFormat::MsgPack)
+        let (status, buffer) = request_client
+            .request_template(
+                &self.url,
+                &endpoint,
+                method,
+                body,
+                None,
+                SerializeFormat::MsgPack,
+            )
             .await?;
 
-        Ok(rmp_serde::from_read(buffer.reader())?)
+        parse_response(status, buffer, &SerializeFormat::MsgPack)
+    }
+}
+
+impl Default for ReplayClient {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(all(test, feature = "mock_game"))]
+mod tests {
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_mock_replay_client() {
+        use crate::mock::game::MockGameServer;
+        use crate::replay::types::Playback;
+        use serde_json::json;
+
+        let server = MockGameServer::builder()
+            .fixture(
+                "/replay/playback",
+                json!({ "length": 1800.0, "paused": false, "seeking": false, "speed": 1.0, "time": 60.0 }),
+            )
+            .start()
+            .await
+            .unwrap();
+        let client = server.replay_client();
+        let request_client = server.request_client();
+
+        let mut playback: Playback = client.get_playback(&request_client).await.unwrap();
+        assert!(!playback.paused);
+
+        playback.paused = true;
+        let playback = client
+            .post_playback(playback, &request_client)
+            .await
+            .unwrap();
+        assert!(playback.paused);
+        assert_eq!(server.fixture("/replay/playback").unwrap()["paused"], true);
+
+        let error = client.get_render(&request_client).await.unwrap_err();
+        assert_eq!(error.error_code(), Some("RESOURCE_NOT_FOUND"));
     }
 }
@@ -1,6 +1,7 @@
 //! Module containing all the data for the rest LCU bindings
 
 pub mod types;
+pub mod watcher;
 
 use serde::de::DeserializeOwned;
 use serde::Serialize;
@@ -8,9 +9,9 @@ use std::borrow::Cow;
 use std::net::SocketAddr;
 
 use crate::rest::request_builder::RequestBuilder;
-use crate::utils::process_info::{CLIENT_PROCESS_NAME, GAME_PROCESS_NAME};
-use crate::utils::requests::SerializeFormat;
-use crate::{utils::process_info::get_running_client, Error, RequestClient};
+use crate::utils::credentials::{CredentialSource, RunningClient};
+use crate::utils::requests::{api_error, Api, SerializeFormat};
+use crate::{Error, RequestClient};
 
 /// Struct representing a connection to the LCU
 pub struct LcuClient {
@@ -109,9 +110,9 @@ pub mod batch {
         ///
         /// # Errors
         /// The value will be an error if the provided type is invalid, or the LCU API is not running
-        pub async fn batched<'a, R>(
+        pub async fn batched<R>(
             &self,
-            requests: &[Request<'a>],
+            requests: &[Request<'_>],
             buffer_size: usize,
             request_client: &RequestClient,
         ) -> Vec<Result<Option<R>, Error>>
@@ -170,7 +171,7 @@ pub mod batch {
         }
 
         #[must_use]
-        pub fn with_client(self, request_client: &RequestClient) -> WithClient {
+        pub fn with_client(self, request_client: &RequestClient) -> WithClient<'_> {
             WithClient {
                 request_client,
                 requests: Vec::new(),
@@ -182,7 +183,7 @@ pub mod batch {
             self,
             request_client: &RequestClient,
             capacity: usize,
-        ) -> WithClient {
+        ) -> WithClient<'_> {
             WithClient {
                 request_client,
                 requests: Vec::with_capacity(capacity),
@@ -227,7 +228,7 @@ pub mod batch {
         }
     }
 
-    impl<'a> WithLcuClient<'a> {
+    impl WithLcuClient<'_> {
         pub async fn execute<R: DeserializeOwned>(self) -> Vec<Result<Option<R>, Error>> {
             self.lcu_client
                 .batched(&self.requests, self.buffer_size, self.request_client)
@@ -248,8 +249,15 @@ impl LcuClient {
     /// the client being down, the lock file being unable to be opened, or the LCU
     /// not running at all
     pub fn new(force_lock_file: bool) -> Result<Self, Error> {
-        let (addr, pass) =
-            get_running_client(CLIENT_PROCESS_NAME, GAME_PROCESS_NAME, force_lock_file)?;
+        Self::new_with_source(&RunningClient::new(force_lock_file))
+    }
+
+    /// Attempts to create a connection to the LCU, using the credentials from the given source
+    ///
+    /// # Errors
+    /// This will return an error if the source is unable to find the credentials
+    pub fn new_with_source(source: &impl CredentialSource) -> Result<Self, Error> {
+        let (addr, pass) = source.credentials()?;
 
         Ok(Self::new_with_credentials(addr, pass))
     }
@@ -267,8 +275,15 @@ impl LcuClient {
     /// This will return an error if the lock file is inaccessible, or if
     /// the LCU is not running
     pub fn reconnect(&mut self, force_lock_file: bool) -> Result<(), Error> {
-        let (addr, pass) =
-            get_running_client(CLIENT_PROCESS_NAME, GAME_PROCESS_NAME, force_lock_file)?;
+        self.reconnect_with_source(&RunningClient::new(force_lock_file))
+    }
+
+    /// Queries the given source, getting a new url and auth header
+    ///
+    /// # Errors
+    /// This will return an error if the source is unable to find the credentials
+    pub fn reconnect_with_source(&mut self, source: &impl CredentialSource) -> Result<(), Error> {
+        let (addr, pass) = source.credentials()?;
         self.reconnect_with_credentials(addr, pass);
         Ok(())
     }
@@ -311,11 +326,14 @@ impl LcuClient {
     }
 
     /// Sends a get request to the LCU
-    /// ```
+    /// ```no_run
+    /// # async fn example() -> Result<(), irelia::Error> {
     /// let request_client = irelia::RequestClient::new();
     /// let lcu_client = irelia::rest::LcuClient::new(false)?;
     ///
-    /// let response: Option<serde_json::Value> = lcu_client.get("/example/endpoint/", &request_client)?;
+    /// let response: Option<serde_json::Value> = lcu_client.get("/example/endpoint/", &request_client).await?;
+    /// # Ok(())
+    /// # }
     /// ```
     ///
     /// # Errors
@@ -421,6 +439,8 @@ impl LcuClient {
     /// This will return an error if the LCU API is not running, or the provided type or body is invalid
     ///
     /// If the response body is empty, this will return an unexpected EOF error
+    ///
+    /// If the LCU responds with an error status, this will return `Error::Lcu`
     pub async fn lcu_request<T: Serialize, R: DeserializeOwned>(
         &self,
         endpoint: &str,
@@ -430,7 +450,7 @@ impl LcuClient {
     ) -> Result<R, Error> {
         use hyper::body::Buf;
 
-        let buf = request_client
+        let (status, buf) = request_client
             .request_template(
                 &self.url_string(),
                 endpoint,
@@ -441,10 +461,20 @@ impl LcuClient {
             )
             .await?;
 
+        if !status.is_success() {
+            return Err(lcu_error(status, buf));
+        }
+
         Ok(serde_json::from_reader(buf.reader())?)
     }
 }
 
+/// Turns an error response from the LCU into `Error::Lcu`
+fn lcu_error(status: hyper::StatusCode, mut buf: impl hyper::body::Buf) -> Error {
+    let body = buf.copy_to_bytes(buf.remaining());
+    api_error(Api::Lcu, status, &body)
+}
+
 mod request_builder {
     use crate::rest::Method;
     use serde::Serialize;
@@ -578,6 +608,7 @@ mod tests {
     use crate::RequestClient;
 
     #[tokio::test]
+    #[ignore = "This needs the League client to be running"]
     async fn batch_test() {
         use crate::rest::{
             batch::{Request, RequestType},
@@ -641,7 +672,144 @@ mod tests {
         println!("{a:?}");
     }
 
+    #[test]
+    fn test_lcu_error() {
+        use crate::Error;
+        use hyper::StatusCode;
+
+        let body = br#"{"errorCode":"RPC_ERROR","httpStatus":404,"implementationDetails":{},"message":"Invalid URI format"}"#;
+
+        let Error::Lcu {
+            status,
+            error_code,
+            message,
+            http_status,
+        } = super::lcu_error(StatusCode::NOT_FOUND, &body[..])
+        else {
+            unreachable!()
+        };
+
+        assert_eq!(status, StatusCode::NOT_FOUND);
+        assert_eq!(error_code, "RPC_ERROR");
+        assert_eq!(message, "Invalid URI format");
+        assert_eq!(http_status, 404);
+
+        let Error::Lcu {
+            error_code,
+            message,
+            http_status,
+            ..
+        } = super::lcu_error(StatusCode::INTERNAL_SERVER_ERROR, &b"oops"[..])
+        else {
+            unreachable!()
+        };
+
+        assert!(error_code.is_empty());
+        assert_eq!(message, "oops");
+        assert_eq!(http_status, 500);
+    }
+
+    #[cfg(feature = "mock")]
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_mock_requests() {
+        use crate::mock::MockServer;
+        use crate::rest::LcuClient;
+        use crate::Error;
+        use hyper::StatusCode;
+        use serde_json::{json, Value};
+
+        let summoner = json!({ "summonerId": 1234, "gameName": "Irelia" });
+
+        let server = MockServer::builder()
+            .route(
+                hyper::Method::GET,
+                "/lol-summoner/v1/current-summoner",
+                StatusCode::OK,
+                summoner.clone(),
+            )
+            .route(
+                hyper::Method::PUT,
+                "/lol-item-sets/v1/item-sets/1234/sets",
+                StatusCode::OK,
+                json!({ "itemSets": [] }),
+            )
+            .route(
+                hyper::Method::POST,
+                "/lol-item-sets/v1/item-sets/1234/sets",
+                StatusCode::OK,
+                json!({ "itemSets": [] }),
+            )
+            .start()
+            .await
+            .unwrap();
+
+        let client = server.request_client();
+        let lcu_client = server.lcu_client();
+
+        let response: Option<Value> = lcu_client
+            .get("/lol-summoner/v1/current-summoner", &client)
+            .await
+            .unwrap();
+        assert_eq!(response, Some(summoner));
+
+        let Err(Error::Lcu {
+            status, message, ..
+        }) = lcu_client
+            .get::<Value>("/lol-missing/v1/endpoint", &client)
+            .await
+        else {
+            panic!("unknown routes should be a 404");
+        };
+        assert_eq!(status, StatusCode::NOT_FOUND);
+        assert_eq!(message, "Invalid URI format");
+
+        let body = json!({ "itemSets": [] });
+
+        #[cfg(feature = "batched")]
+        {
+            use crate::rest::batch::Request;
+
+            let requests = [
+                Request::get("/lol-summoner/v1/current-summoner"),
+                Request::put("/lol-item-sets/v1/item-sets/1234/sets", Some(&body)),
+            ];
+            let results = lcu_client.batched::<Value>(&requests, 2, &client).await;
+            assert_eq!(results.len(), 2);
+            assert!(results.iter().all(Result::is_ok));
+        }
+
+        let request = super::Request::builder()
+            .method(Method::Post)
+            .endpoint("/lol-item-sets/v1/item-sets/1234/sets")
+            .body(&body)
+            .build();
+        let response: Value = lcu_client.request(request, &client).await.unwrap();
+        assert_eq!(response, body);
+
+        let recorded = server.requests();
+        let post = recorded.last().unwrap();
+        assert_eq!(post.method, hyper::Method::POST);
+        assert_eq!(post.path, "/lol-item-sets/v1/item-sets/1234/sets");
+        assert_eq!(post.body.as_ref(), Some(&body));
+
+        // The wrong password never reaches the routes
+        let wrong_password =
+            LcuClient::new_with_credentials(server.addr(), "Basic bm9wZQ==".into());
+        let response = wrong_password
+            .get::<Value>("/lol-summoner/v1/current-summoner", &client)
+            .await;
+        assert!(matches!(
+            response,
+            Err(Error::Lcu {
+                status: StatusCode::UNAUTHORIZED,
+                ..
+            })
+        ));
+        assert_eq!(server.requests().len(), recorded.len());
+    }
+
     #[tokio::test]
+    #[ignore = "This needs a connection to GitHub"]
     async fn test_schema_des() {
         let _schema = super::schema(
             "https://raw.githubusercontent.com/dysolix/hasagi-types/main/swagger.json",
@@ -0,0 +1,291 @@
+//! Watches for the client starting, stopping, or restarting
+//!
+//! By default this reads the lock file of the running client, and emits an event
+//! every time the credentials it contains appear, disappear, or change
+
+use std::net::SocketAddr;
+use std::ops::ControlFlow;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, RwLock};
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+
+use crate::rest::LcuClient;
+use crate::utils::credentials::{CredentialSource, RunningClient};
+
+/// Lifecycle events emitted by the `Watcher`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ClientEvent {
+    /// The client was found, after either not running or the watcher starting
+    ClientUp { addr: SocketAddr, auth: String },
+    /// The client is no longer running
+    ClientDown,
+    /// The client is still running, but the port or password changed, usually
+    /// because it restarted between two polls
+    CredentialsChanged { addr: SocketAddr, auth: String },
+}
+
+/// Polls a credential source, keeping track of the last credentials it returned
+pub struct Watcher {
+    source: Box<dyn CredentialSource>,
+    current: Option<(SocketAddr, String)>,
+}
+
+impl Watcher {
+    #[must_use]
+    /// Creates a watcher that always reads the lock file of the running client
+    pub fn new() -> Self {
+        Self::with_source(RunningClient::new(true))
+    }
+
+    #[must_use]
+    /// Creates a watcher that polls the given source
+    pub fn with_source(source: impl CredentialSource + 'static) -> Self {
+        Self {
+            source: Box::new(source),
+            current: None,
+        }
+    }
+
+    #[must_use]
+    /// Returns the credentials seen on the last poll, if the client was running
+    pub fn current(&self) -> Option<&(SocketAddr, String)> {
+        self.current.as_ref()
+    }
+
+    /// Queries the source once, returning an event if anything changed since the last poll
+    ///
+    /// Any error from the source is treated as the client not running
+    pub fn poll(&mut self) -> Option<ClientEvent> {
+        let next = self.source.credentials().ok();
+
+        let event = match (&self.current, &next) {
+            (None, Some((addr, auth))) => ClientEvent::ClientUp {
+                addr: *addr,
+                auth: auth.clone(),
+            },
+            (Some(_), None) => ClientEvent::ClientDown,
+            (Some(current), Some((addr, auth))) if current.0 != *addr || &current.1 != auth => {
+                ClientEvent::CredentialsChanged {
+                    addr: *addr,
+                    auth: auth.clone(),
+                }
+            }
+            _ => return None,
+        };
+
+        self.current = next;
+
+        // The auth header is left out on purpose
+        #[cfg(feature = "tracing")]
+        match &event {
+            ClientEvent::ClientUp { addr, .. } => tracing::info!(%addr, "client up"),
+            ClientEvent::ClientDown => tracing::info!("client down"),
+            ClientEvent::CredentialsChanged { addr, .. } => {
+                tracing::info!(%addr, "client credentials changed");
+            }
+        }
+
+        Some(event)
+    }
+
+    /// Same as `poll`, but also points the client at the new credentials
+    pub fn poll_and_reconnect(&mut self, client: &mut LcuClient) -> Option<ClientEvent> {
+        let event = self.poll()?;
+        reconnect(client, &event);
+        Some(event)
+    }
+
+    /// Polls the source on a new thread, calling the callback for every event
+    ///
+    /// The thread stops when the callback returns `ControlFlow::Break`,
+    /// or when `WatcherHandle::stop` is called
+    pub fn spawn<F>(self, interval: Duration, mut callback: F) -> WatcherHandle
+    where
+        F: FnMut(&ClientEvent) -> ControlFlow<()> + Send + 'static,
+    {
+        self.spawn_inner(interval, move |watcher| match watcher.poll() {
+            Some(event) => callback(&event),
+            None => ControlFlow::Continue(()),
+        })
+    }
+
+    /// Same as `spawn`, but the client is reconnected before the callback is called,
+    /// so long-running tools keep working across client restarts
+    pub fn spawn_with_client<F>(
+        self,
+        interval: Duration,
+        client: Arc<RwLock<LcuClient>>,
+        mut callback: F,
+    ) -> WatcherHandle
+    where
+        F: FnMut(&ClientEvent) -> ControlFlow<()> + Send + 'static,
+    {
+        self.spawn_inner(interval, move |watcher| {
+            // The source is polled without the lock, as finding the client can take a while
+            let Some(event) = watcher.poll() else {
+                return ControlFlow::Continue(());
+            };
+
+            // A poisoned lock only means another thread panicked mid-update,
+            // the credentials are about to be overwritten either way
+            reconnect(
+                &mut client
+                    .write()
+                    .unwrap_or_else(std::sync::PoisonError::into_inner),
+                &event,
+            );
+
+            callback(&event)
+        })
+    }
+
+    fn spawn_inner<F>(mut self, interval: Duration, mut tick: F) -> WatcherHandle
+    where
+        F: FnMut(&mut Self) -> ControlFlow<()> + Send + 'static,
+    {
+        let stop = Arc::new(AtomicBool::new(false));
+        let thread_stop = stop.clone();
+
+        let handle = thread::spawn(move || {
+            while !thread_stop.load(Ordering::Acquire) {
+                if tick(&mut self).is_break() {
+                    break;
+                }
+
+                // Parking lets `stop` wake the thread early
+                thread::park_timeout(interval);
+            }
+        });
+
+        WatcherHandle { stop, handle }
+    }
+}
+
+/// Points the client at the credentials carried by the event, if there are any
+fn reconnect(client: &mut LcuClient, event: &ClientEvent) {
+    if let ClientEvent::ClientUp { addr, auth } | ClientEvent::CredentialsChanged { addr, auth } =
+        event
+    {
+        client.reconnect_with_credentials(*addr, auth.clone());
+    }
+}
+
+impl Default for Watcher {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Handle to a watcher running on its own thread
+pub struct WatcherHandle {
+    stop: Arc<AtomicBool>,
+    handle: JoinHandle<()>,
+}
+
+impl WatcherHandle {
+    #[must_use]
+    /// Stops the watcher, and waits for the thread to exit
+    pub fn stop(self) -> Option<()> {
+        self.stop.store(true, Ordering::Release);
+        self.handle.thread().unpark();
+        self.handle.join().ok()
+    }
+
+    #[must_use]
+    pub fn is_finished(&self) -> bool {
+        self.handle.is_finished()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ClientEvent, Watcher};
+    use crate::process_info::{self, ErrorKind};
+    use crate::rest::LcuClient;
+    use std::net::{IpAddr, Ipv4Addr, SocketAddr};
+    use std::ops::ControlFlow;
+    use std::sync::{mpsc, Arc, Mutex, RwLock};
+    use std::time::Duration;
+
+    #[test]
+    fn test_watcher_events() {
+        let state: Arc<Mutex<Option<(SocketAddr, String)>>> = Arc::default();
+        let source_state = state.clone();
+
+        let mut watcher = Watcher::with_source(move || {
+            source_state
+                .lock()
+                .unwrap()
+                .clone()
+                .ok_or(process_info::Error::new(
+                    ErrorKind::NotRunning,
+                    "not running",
+                ))
+        });
+
+        let addr = SocketAddr::new(IpAddr::V4(Ipv4Addr::LOCALHOST), 1234);
+        let mut client = LcuClient::new_with_credentials(addr, String::new());
+
+        assert_eq!(watcher.poll(), None);
+
+        *state.lock().unwrap() = Some((addr, "Basic first".into()));
+        assert_eq!(
+            watcher.poll_and_reconnect(&mut client),
+            Some(ClientEvent::ClientUp {
+                addr,
+                auth: "Basic first".into()
+            })
+        );
+        assert_eq!(client.auth_header(), "Basic first");
+        assert_eq!(watcher.poll(), None);
+
+        let new_addr = SocketAddr::new(IpAddr::V4(Ipv4Addr::LOCALHOST), 4321);
+        *state.lock().unwrap() = Some((new_addr, "Basic second".into()));
+        assert_eq!(
+            watcher.poll_and_reconnect(&mut client),
+            Some(ClientEvent::CredentialsChanged {
+                addr: new_addr,
+                auth: "Basic second".into()
+            })
+        );
+        assert_eq!(client.url(), &new_addr);
+
+        *state.lock().unwrap() = None;
+        assert_eq!(watcher.poll(), Some(ClientEvent::ClientDown));
+        assert_eq!(watcher.current(), None);
+    }
+
+    #[test]
+    fn test_spawn_with_client_polls_unlocked() {
+        let addr = SocketAddr::new(IpAddr::V4(Ipv4Addr::LOCALHOST), 1234);
+        let client = Arc::new(RwLock::new(LcuClient::new_with_credentials(
+            addr,
+            String::new(),
+        )));
+        let source_client = client.clone();
+
+        // Readers of the client must not be blocked while the source is polled
+        let watcher = Watcher::with_source(move || {
+            assert!(source_client.try_read().is_ok());
+            Ok((addr, "Basic polled".to_string()))
+        });
+
+        let (sender, receiver) = mpsc::channel();
+        let handle =
+            watcher.spawn_with_client(Duration::from_millis(10), client.clone(), move |event| {
+                sender.send(event.clone()).unwrap();
+                ControlFlow::Break(())
+            });
+
+        assert_eq!(
+            receiver.recv_timeout(Duration::from_secs(5)).unwrap(),
+            ClientEvent::ClientUp {
+                addr,
+                auth: "Basic polled".into()
+            }
+        );
+        assert!(handle.stop().is_some());
+        assert_eq!(client.read().unwrap().auth_header(), "Basic polled");
+    }
+}
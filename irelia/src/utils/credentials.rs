@@ -0,0 +1,203 @@
+//! Different ways of finding the address and auth header of the LCU
+//!
+//! By default both `LcuClient` and `LCUWebSocket` look for the running client process,
+//! but any type implementing `CredentialSource` can be used instead, for example
+//! to point them at a mock server, or at a client found some other way
+
+use std::borrow::Cow;
+use std::net::SocketAddr;
+use std::path::PathBuf;
+
+use crate::utils::process_info::{
+    self, auth_header, format_credentials, get_lock_file_credentials, get_running_client,
+    ErrorKind, CLIENT_PROCESS_NAME, GAME_PROCESS_NAME,
+};
+
+/// A source of the address and auth header used to connect to the LCU
+pub trait CredentialSource: Send + Sync {
+    /// Returns the address of the LCU, and the formatted basic auth header
+    ///
+    /// # Errors
+    /// This will return an error if the credentials could not be found
+    fn credentials(&self) -> Result<(SocketAddr, String), process_info::Error>;
+}
+
+impl<F> CredentialSource for F
+where
+    F: Fn() -> Result<(SocketAddr, String), process_info::Error> + Send + Sync,
+{
+    fn credentials(&self) -> Result<(SocketAddr, String), process_info::Error> {
+        self()
+    }
+}
+
+/// Reads the credentials from the arguments of the running client process,
+/// or from the lock file next to it, this is the default source
+pub struct RunningClient {
+    client_process_name: Cow<'static, str>,
+    game_process_name: Cow<'static, str>,
+    force_lock_file: bool,
+}
+
+impl RunningClient {
+    #[must_use]
+    /// Uses the default process names for the current platform
+    ///
+    /// `force_lock_file` will read the lock file regardless of whether the client
+    /// or the game is running at the time
+    pub fn new(force_lock_file: bool) -> Self {
+        Self {
+            client_process_name: CLIENT_PROCESS_NAME.into(),
+            game_process_name: GAME_PROCESS_NAME.into(),
+            force_lock_file,
+        }
+    }
+
+    #[must_use]
+    /// Overrides the names of the client and game processes that are searched for
+    pub fn with_process_names(
+        mut self,
+        client_process_name: impl Into<Cow<'static, str>>,
+        game_process_name: impl Into<Cow<'static, str>>,
+    ) -> Self {
+        self.client_process_name = client_process_name.into();
+        self.game_process_name = game_process_name.into();
+
+        self
+    }
+}
+
+impl Default for RunningClient {
+    fn default() -> Self {
+        Self::new(false)
+    }
+}
+
+impl CredentialSource for RunningClient {
+    fn credentials(&self) -> Result<(SocketAddr, String), process_info::Error> {
+        get_running_client(
+            &self.client_process_name,
+            &self.game_process_name,
+            self.force_lock_file,
+        )
+    }
+}
+
+/// Reads the credentials from a lock file at a known path
+pub struct LockFilePath(pub PathBuf);
+
+impl LockFilePath {
+    #[must_use]
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self(path.into())
+    }
+}
+
+impl CredentialSource for LockFilePath {
+    fn credentials(&self) -> Result<(SocketAddr, String), process_info::Error> {
+        get_lock_file_credentials(&self.0)
+    }
+}
+
+/// Reads the port and password from environment variables,
+/// by default these are `LCU_PORT` and `LCU_PASSWORD`
+pub struct EnvVars {
+    port: Cow<'static, str>,
+    password: Cow<'static, str>,
+}
+
+impl EnvVars {
+    #[must_use]
+    pub fn new() -> Self {
+        Self::with_names("LCU_PORT", "LCU_PASSWORD")
+    }
+
+    #[must_use]
+    /// Uses the given names for the port and password variables
+    pub fn with_names(
+        port: impl Into<Cow<'static, str>>,
+        password: impl Into<Cow<'static, str>>,
+    ) -> Self {
+        Self {
+            port: port.into(),
+            password: password.into(),
+        }
+    }
+}
+
+impl Default for EnvVars {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl CredentialSource for EnvVars {
+    fn credentials(&self) -> Result<(SocketAddr, String), process_info::Error> {
+        let port = std::env::var(&*self.port).map_err(|err| {
+            process_info::Error::new_string(
+                ErrorKind::PortNotFound,
+                format!("{}: {err}", self.port),
+            )
+        })?;
+        let password = std::env::var(&*self.password).map_err(|err| {
+            process_info::Error::new_string(
+                ErrorKind::AuthTokenNotFound,
+                format!("{}: {err}", self.password),
+            )
+        })?;
+
+        format_credentials(&port, &password)
+    }
+}
+
+/// Always returns the same credentials, which are implicitly trusted
+#[derive(Clone)]
+pub struct Fixed {
+    addr: SocketAddr,
+    auth_header: String,
+}
+
+impl Fixed {
+    #[must_use]
+    /// Uses an already formatted auth header
+    pub fn new(addr: SocketAddr, auth_header: String) -> Self {
+        Self { addr, auth_header }
+    }
+
+    #[must_use]
+    /// Encodes the raw password into the auth header
+    pub fn from_password(addr: SocketAddr, password: &str) -> Self {
+        Self::new(addr, auth_header(password))
+    }
+}
+
+impl CredentialSource for Fixed {
+    fn credentials(&self) -> Result<(SocketAddr, String), process_info::Error> {
+        Ok((self.addr, self.auth_header.clone()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{CredentialSource, EnvVars, Fixed, LockFilePath};
+    use std::net::{IpAddr, Ipv4Addr, SocketAddr};
+
+    #[test]
+    fn test_sources() {
+        let addr = SocketAddr::new(IpAddr::V4(Ipv4Addr::LOCALHOST), 2999);
+
+        let fixed = Fixed::from_password(addr, "password");
+        let (port, auth) = fixed.credentials().unwrap();
+        assert_eq!(port, addr);
+        assert_eq!(auth, "Basic cmlvdDpwYXNzd29yZA==");
+
+        let path = std::env::temp_dir().join("irelia_test_sources_lockfile");
+        std::fs::write(&path, "LeagueClient:1234:2999:password:https").unwrap();
+        let lock_file = LockFilePath::new(&path).credentials().unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(lock_file, (addr, auth));
+
+        let missing = EnvVars::with_names("IRELIA_MISSING_PORT", "IRELIA_MISSING_PASSWORD");
+        assert!(missing.credentials().is_err());
+    }
+}
@@ -1,4 +1,6 @@
 #[cfg(any(feature = "ws", feature = "rest"))]
+pub mod credentials;
+#[cfg(any(feature = "ws", feature = "rest"))]
 pub mod process_info;
 #[cfg(any(feature = "in_game", feature = "rest"))]
 pub(crate) mod requests;
@@ -2,25 +2,29 @@
 //! <https://hextechdocs.dev/getting-started-with-the-lcu-api/>
 
 //! This module also contains a list of constants for the different names
-//! of the processes for `OSX`, and `Windows`
+//! of the processes for `OSX`, `Windows`, and `Linux` (running under Wine)
 
 use irelia_encoder::Encoder;
 use std::fmt::{Display, Formatter};
-use std::io::Read;
 use std::net::{IpAddr, Ipv4Addr, SocketAddr};
 use std::num::ParseIntError;
-use sysinfo::{ProcessRefreshKind, RefreshKind, System};
+use std::path::{Path, PathBuf};
+use sysinfo::{Process, ProcessRefreshKind, RefreshKind, System};
 
-// Linux will be unplayable soon, so support has been removed
 #[cfg(target_os = "windows")]
 pub const CLIENT_PROCESS_NAME: &str = "LeagueClientUx.exe";
 #[cfg(target_os = "macos")]
 pub const CLIENT_PROCESS_NAME: &str = "LeagueClientUx";
+// Linux is only supported through Wine, so the processes keep their Windows names
+#[cfg(target_os = "linux")]
+pub const CLIENT_PROCESS_NAME: &str = "LeagueClientUx.exe";
 
 #[cfg(target_os = "windows")]
 pub const GAME_PROCESS_NAME: &str = "League of Legends.exe";
 #[cfg(target_os = "macos")]
 pub const GAME_PROCESS_NAME: &str = "League of Legends";
+#[cfg(target_os = "linux")]
+pub const GAME_PROCESS_NAME: &str = "League of Legends.exe";
 
 /// const copy of the encoder
 pub(crate) const ENCODER: Encoder = Encoder::new();
@@ -40,8 +44,69 @@ pub fn get_running_client(
     game_process_name: &str,
     force_lock_file: bool,
 ) -> Result<(SocketAddr, String), Error> {
-    // If we always read the lock file, we never need to get the command line of the process
-    let cmd = if force_lock_file {
+    let system = processes(force_lock_file);
+    let (process, client) = find_process(&system, client_process_name, game_process_name)?;
+
+    if !client || force_lock_file {
+        return Ok(LockFile::from_path(lock_file_path(process, client)?)?.credentials());
+    }
+
+    let cmd = process.cmd();
+    // Use a variable in a higher scope to make sure that port and auth get initialized
+    let mut scoped_auth = None;
+    let mut scoped_port = None;
+
+    // Iterate through the command args, updating the scoped values as we go
+    for s in cmd {
+        if scoped_auth.is_none() {
+            scoped_auth = s.strip_prefix("--remoting-auth-token=");
+        }
+
+        if scoped_port.is_none() {
+            scoped_port = s.strip_prefix("--app-port=");
+        }
+
+        if scoped_auth.is_some() && scoped_port.is_some() {
+            break;
+        }
+    }
+
+    // Check that we found a port and auth key, otherwise error
+    let port = scoped_port.ok_or(Error::new(
+        ErrorKind::PortNotFound,
+        "port was not found in command line",
+    ))?;
+    let auth = scoped_auth.ok_or(Error::new(
+        ErrorKind::AuthTokenNotFound,
+        "auth token was not found in command line",
+    ))?;
+
+    format_credentials(port, auth)
+}
+
+/// Finds the lock file of the running client or game, and parses it
+///
+/// Unlike `get_running_client` this keeps every field of the lock file,
+/// such as the PID of the client and the protocol in use
+///
+/// # Errors
+/// This will return an error if neither process is running,
+/// or if the lock file is inaccessible or invalid
+pub fn get_running_lock_file(
+    client_process_name: &str,
+    game_process_name: &str,
+) -> Result<LockFile, Error> {
+    let system = processes(true);
+    let (process, client) = find_process(&system, client_process_name, game_process_name)?;
+
+    LockFile::from_path(lock_file_path(process, client)?)
+}
+
+/// Gets the current list of processes, with everything needed to find the client
+fn processes(force_lock_file: bool) -> System {
+    // If we always read the lock file, we never need to get the command line of the process,
+    // unless we're running under Wine, where it's needed to find the process in the first place
+    let cmd = if force_lock_file && !cfg!(target_os = "linux") {
         sysinfo::UpdateKind::Never
     } else {
         sysinfo::UpdateKind::OnlyIfNotSet
@@ -50,14 +115,24 @@ pub fn get_running_client(
     let refresh_kind = ProcessRefreshKind::new()
         .with_exe(sysinfo::UpdateKind::OnlyIfNotSet)
         .with_cmd(cmd);
+    // Wine stores the prefix in the environment, which is needed to find the lock file
+    #[cfg(target_os = "linux")]
+    let refresh_kind = refresh_kind.with_environ(sysinfo::UpdateKind::OnlyIfNotSet);
 
     // Get the current list of processes
-    let system = System::new_with_specifics(
+    System::new_with_specifics(
         // This creates a new instance of `system` every time, so this only
         //  needs to be updated if it's not set
         RefreshKind::new().with_processes(refresh_kind),
-    );
+    )
+}
 
+/// Finds either the client or the game process, returning true if it is the client
+fn find_process<'a>(
+    system: &'a System,
+    client_process_name: &str,
+    game_process_name: &str,
+) -> Result<(&'a Process, bool), Error> {
     // Is the client running, or is it the game?
     let mut client = false;
 
@@ -68,15 +143,13 @@ pub fn get_running_client(
         .processes()
         .values()
         .find(|process| {
-            // Get the name of the process
-            let name = process.name();
             // If it matches the name of the client,
             // set the flag, and return it
-            if name == client_process_name {
+            if is_process(process, client_process_name) {
                 client = true;
                 client
             } else {
-                name == game_process_name
+                is_process(process, game_process_name)
             }
         })
         .ok_or(Error::new(
@@ -84,117 +157,313 @@ pub fn get_running_client(
             "neither the game or client process were running",
         ))?;
 
-    // The size of the lock file is typically 53kb, but I am overallocating to stay cautious
-    let mut lock_file: [u8; 60] = [0; 60];
-    let port: &str;
-    let auth: &str;
-
-    if client && !force_lock_file {
-        let cmd = process.cmd();
-        // Use a variable in a higher scope to make sure that port and auth get initialized
-        let mut scoped_auth = None;
-        let mut scoped_port = None;
-
-        // Iterate through the command args, updating the scoped values as we go
-        for s in cmd {
-            if scoped_auth.is_none() {
-                scoped_auth = s.strip_prefix("--remoting-auth-token=");
-            }
+    Ok((process, client))
+}
 
-            if scoped_port.is_none() {
-                scoped_port = s.strip_prefix("--app-port=");
-            }
+/// Returns the path to the lock file, relative to the path of the client or game
+fn lock_file_path(process: &Process, client: bool) -> Result<PathBuf, Error> {
+    const LOCK_FILE_NOT_FOUND_ERROR: Error = Error::new(
+        ErrorKind::LockFileNotFound,
+        "Did not follow the typical install structure",
+    );
 
-            if scoped_auth.is_some() && scoped_port.is_some() {
-                break;
-            }
-        }
+    // We have to walk back twice to get the path of the lock file relative to the path of the game
+    // This can only be None on Linux according to the docs, so we should be fine everywhere else
+    let path = executable_path(process).ok_or(LOCK_FILE_NOT_FOUND_ERROR)?;
 
-        // Check that we found a port and auth key, otherwise error
-        port = scoped_port.ok_or(Error::new(
-            ErrorKind::PortNotFound,
-            "port was not found in command line",
-        ))?;
-        auth = scoped_auth.ok_or(Error::new(
-            ErrorKind::AuthTokenNotFound,
-            "auth token was not found in command line",
-        ))?;
+    let dir = path.parent().ok_or(LOCK_FILE_NOT_FOUND_ERROR)?;
+    // Sadly, we're relying on how the client structures things here
+    // Walking back a whole folder in order to get the lock file
+    let base_dir = if client {
+        // If it IS the client, we're in the right dir
+        dir
     } else {
-        const LOCK_FILE_NOT_FOUND_ERROR: Error = Error::new(
-            ErrorKind::LockFileNotFound,
-            "Did not follow the typical install structure",
-        );
+        // Otherwise it is the game, and we need to go back once
+        dir.parent().ok_or(LOCK_FILE_NOT_FOUND_ERROR)?
+    };
 
-        // We have to walk back twice to get the path of the lock file relative to the path of the game
-        // This can only be None on Linux according to the docs, so we should be fine everywhere else
-        let path = process.exe().ok_or(LOCK_FILE_NOT_FOUND_ERROR)?;
-
-        let dir = path.parent().ok_or(LOCK_FILE_NOT_FOUND_ERROR)?;
-        // Sadly, we're relying on how the client structures things here
-        // Walking back a whole folder in order to get the lock file
-        let base_dir = if client {
-            // If it IS the client, we're in the right dir
-            dir
-        } else {
-            // Otherwise it is the game, and we need to go back once
-            dir.parent().ok_or(LOCK_FILE_NOT_FOUND_ERROR)?
-        };
+    Ok(base_dir.join("lockfile"))
+}
 
-        let mut file = std::fs::File::open(base_dir.join("lockfile"))?;
-        // This len shouldn't be more than a few bytes
-        #[allow(clippy::cast_possible_truncation)]
-        let len = file.metadata()?.len() as usize;
+/// Reads the port and auth from the lock file at the given path
+///
+/// # Errors
+/// This will return an error if the lock file cannot be read,
+/// or if it is not a valid lock file
+pub fn get_lock_file_credentials(path: impl AsRef<Path>) -> Result<(SocketAddr, String), Error> {
+    Ok(LockFile::from_path(path)?.credentials())
+}
 
-        // Read the file initially
-        let mut read = file.read(&mut lock_file)?;
+/// The protocol the LCU is being served over, as listed in the lock file
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Protocol {
+    Http,
+    Https,
+}
 
-        // Make sure the entire file was read, though it is so small I can't imagine it wouldn't be
-        while read != len {
-            read += file.read(&mut lock_file[read..])?;
+impl Protocol {
+    #[must_use]
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Protocol::Http => "http",
+            Protocol::Https => "https",
         }
+    }
+}
 
-        // Make sure that we're not over reading into 0's
-        let lock_file = std::str::from_utf8(&lock_file[..len])?;
+/// The contents of the lock file, which is written by the client
+/// in the format `name:pid:port:password:protocol`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LockFile {
+    name: String,
+    pid: u32,
+    port: u16,
+    password: String,
+    protocol: Protocol,
+}
+
+impl LockFile {
+    /// Parses the contents of a lock file
+    ///
+    /// # Errors
+    /// This will return `ErrorKind::LockFileTruncated` if any of the five fields are missing,
+    /// and `ErrorKind::LockFileMalformed` if any of them are invalid
+    pub fn parse(lock_file: &str) -> Result<LockFile, Error> {
+        const TRUNCATED: Error = Error::new(
+            ErrorKind::LockFileTruncated,
+            "lock file did not contain all five fields",
+        );
+
+        let malformed = |message: String| Error::new_string(ErrorKind::LockFileMalformed, message);
 
         // Split the lock file on `:` which separates the different fields
-        // Because lock_file is from a higher scope, we can split the string here
-        // and return two string references later on
-        let mut split = lock_file.split(':');
-
-        // Get the 3rd field, which should be the port
-        port = split.nth(2).ok_or(Error::new(
-            ErrorKind::PortNotFound,
-            "port was not found in lock file",
-        ))?;
-        // We moved the cursor, so the fourth element is the very next one
-        // Which should be the auth string
-        auth = split.next().ok_or(Error::new(
-            ErrorKind::AuthTokenNotFound,
-            "password was not found in lock file",
-        ))?;
+        let mut split = lock_file.trim_end().split(':');
+
+        let name = split
+            .next()
+            .filter(|name| !name.is_empty())
+            .ok_or(TRUNCATED)?;
+        let pid = split.next().ok_or(TRUNCATED)?;
+        let port = split.next().ok_or(TRUNCATED)?;
+        let password = split.next().ok_or(TRUNCATED)?;
+        let protocol = split.next().ok_or(TRUNCATED)?;
+
+        if split.next().is_some() {
+            return Err(malformed(format!(
+                "lock file contained more than five fields: {lock_file:?}"
+            )));
+        }
+
+        let pid = pid
+            .parse()
+            .map_err(|err: ParseIntError| malformed(format!("invalid pid {pid:?}: {err}")))?;
+        let port = port
+            .parse()
+            .map_err(|err: ParseIntError| malformed(format!("invalid port {port:?}: {err}")))?;
+
+        if password.is_empty() {
+            return Err(malformed("lock file contained an empty password".into()));
+        }
+
+        let protocol = match protocol {
+            "https" => Protocol::Https,
+            "http" => Protocol::Http,
+            protocol => return Err(malformed(format!("unknown protocol {protocol:?}"))),
+        };
+
+        Ok(LockFile {
+            name: name.to_owned(),
+            pid,
+            port,
+            password: password.to_owned(),
+            protocol,
+        })
     }
 
-    // Format the header without
-    let mut needs_encoding = String::with_capacity(5 + auth.len());
-    needs_encoding.push_str("riot:");
-    needs_encoding.push_str(auth);
+    /// Reads and parses the lock file at the given path
+    ///
+    /// # Errors
+    /// This will return an error if the file cannot be read, or if `LockFile::parse` fails
+    pub fn from_path(path: impl AsRef<Path>) -> Result<LockFile, Error> {
+        let bytes = std::fs::read(path)?;
 
-    // The auth header has to be base64 encoded, so that's happens here
-    let auth_header = ENCODER.encode(needs_encoding);
+        Self::parse(std::str::from_utf8(&bytes)?)
+    }
+
+    #[must_use]
+    /// The name of the process that wrote the lock file, usually `LeagueClient`
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    #[must_use]
+    /// The PID of the process that wrote the lock file
+    pub fn pid(&self) -> u32 {
+        self.pid
+    }
 
+    #[must_use]
+    pub fn port(&self) -> u16 {
+        self.port
+    }
+
+    #[must_use]
+    pub fn password(&self) -> &str {
+        &self.password
+    }
+
+    #[must_use]
+    pub fn protocol(&self) -> Protocol {
+        self.protocol
+    }
+
+    #[must_use]
+    /// The LCU is always served on localhost
+    pub fn addr(&self) -> SocketAddr {
+        SocketAddr::new(IpAddr::V4(Ipv4Addr::LOCALHOST), self.port)
+    }
+
+    #[must_use]
+    /// Returns the basic auth header expected by the LCU
+    pub fn auth_header(&self) -> String {
+        auth_header(&self.password)
+    }
+
+    #[must_use]
+    /// Returns the address and auth header, in the same format as `get_running_client`
+    pub fn credentials(&self) -> (SocketAddr, String) {
+        (self.addr(), self.auth_header())
+    }
+}
+
+/// Turns the raw port and password into a localhost address and the basic auth header
+///
+/// # Errors
+/// This will return an error if the port is not a valid number
+pub(crate) fn format_credentials(port: &str, auth: &str) -> Result<(SocketAddr, String), Error> {
     let port: u16 = port.parse().map_err(|err: ParseIntError| {
         Error::new_string(ErrorKind::PortNotFound, err.to_string())
     })?;
 
     let addr = SocketAddr::new(IpAddr::V4(Ipv4Addr::LOCALHOST), port);
 
+    // Format the port and header so that they can be used as headers
+    // For the LCU API
+    Ok((addr, auth_header(auth)))
+}
+
+/// Encodes the password as the basic auth header expected by the LCU
+pub(crate) fn auth_header(password: &str) -> String {
+    // Format the header without
+    let mut needs_encoding = String::with_capacity(5 + password.len());
+    needs_encoding.push_str("riot:");
+    needs_encoding.push_str(password);
+
+    // The auth header has to be base64 encoded, so that's happens here
+    let auth_header = ENCODER.encode(needs_encoding);
+
     let mut formatted_auth = String::with_capacity(6 + auth_header.len());
     formatted_auth.push_str("Basic ");
     formatted_auth.push_str(&auth_header);
 
-    // Format the port and header so that they can be used as headers
-    // For the LCU API
-    Ok((addr, formatted_auth))
+    formatted_auth
+}
+
+/// Checks if the process has the given name
+///
+/// Under Wine the name reported by the kernel is truncated to 15 bytes,
+/// so the Windows path in the command line is checked as well
+fn is_process(process: &Process, process_name: &str) -> bool {
+    has_name(process.name(), process.cmd(), process_name)
+}
+
+/// The checks behind `is_process`, taking the name and command line of the process
+#[cfg_attr(not(target_os = "linux"), allow(unused_variables))]
+fn has_name(name: &str, cmd: &[String], process_name: &str) -> bool {
+    if name == process_name {
+        return true;
+    }
+
+    #[cfg(target_os = "linux")]
+    if let Some(exe) = cmd.first() {
+        return wine::file_name(exe) == process_name;
+    }
+
+    false
+}
+
+/// Returns the path to the executable of the process
+///
+/// Under Wine `exe` points to the Wine loader, so the path
+/// is translated from the command line into the Wine prefix
+fn executable_path(process: &Process) -> Option<PathBuf> {
+    #[cfg(target_os = "linux")]
+    if let Some(path) = wine::executable_path(process) {
+        return Some(path);
+    }
+
+    process.exe().map(Path::to_path_buf)
+}
+
+#[cfg(target_os = "linux")]
+mod wine {
+    use std::path::{Path, PathBuf};
+    use sysinfo::Process;
+
+    /// Returns the last component of a path, using either Windows or Unix separators
+    pub(super) fn file_name(path: &str) -> &str {
+        path.rsplit(['\\', '/']).next().unwrap_or(path)
+    }
+
+    /// Translates a Windows path like `C:\Riot Games\LeagueClientUx.exe` into
+    /// the matching path in the Wine prefix, using the `dosdevices` drive links
+    pub(super) fn to_unix_path(prefix: &Path, windows_path: &str) -> Option<PathBuf> {
+        let (drive, rest) = windows_path.split_once(':')?;
+
+        if drive.len() != 1 || !drive.as_bytes()[0].is_ascii_alphabetic() {
+            return None;
+        }
+
+        let mut path = prefix
+            .join("dosdevices")
+            .join(format!("{}:", drive.to_ascii_lowercase()));
+
+        for component in rest.split(['\\', '/']).filter(|c| !c.is_empty()) {
+            path.push(component);
+        }
+
+        Some(path)
+    }
+
+    /// Gets the Wine prefix from the environment of the process,
+    /// falling back to the default of `~/.wine`
+    pub(super) fn prefix(process: &Process) -> Option<PathBuf> {
+        let var = |key: &str| {
+            process
+                .environ()
+                .iter()
+                .find_map(|var| var.strip_prefix(key)?.strip_prefix('='))
+                .filter(|value| !value.is_empty())
+        };
+
+        if let Some(prefix) = var("WINEPREFIX") {
+            return Some(prefix.into());
+        }
+
+        let home = var("HOME")
+            .map(PathBuf::from)
+            .or_else(|| std::env::var_os("HOME").map(PathBuf::from))?;
+
+        Some(home.join(".wine"))
+    }
+
+    /// Returns the path of the executable inside the Wine prefix,
+    /// or `None` if the process is not running under Wine
+    pub(super) fn executable_path(process: &Process) -> Option<PathBuf> {
+        let exe = process.cmd().first()?;
+
+        to_unix_path(&prefix(process)?, exe)
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -213,14 +482,14 @@ impl Display for Error {
 impl std::error::Error for Error {}
 
 impl Error {
-    const fn new(kind: ErrorKind, message: &'static str) -> Error {
+    pub(crate) const fn new(kind: ErrorKind, message: &'static str) -> Error {
         Self {
             kind,
             message: std::borrow::Cow::Borrowed(message),
         }
     }
 
-    fn new_string(kind: ErrorKind, message: String) -> Error {
+    pub(crate) fn new_string(kind: ErrorKind, message: String) -> Error {
         Self {
             kind,
             message: std::borrow::Cow::Owned(message),
@@ -248,6 +517,8 @@ impl Error {
 pub enum ErrorKind {
     Io(std::io::ErrorKind),
     LockFileNotFound,
+    LockFileTruncated,
+    LockFileMalformed,
     AuthTokenNotFound,
     PortNotFound,
     NotRunning,
@@ -275,9 +546,35 @@ impl From<std::str::Utf8Error> for Error {
 
 #[cfg(test)]
 mod tests {
-    use super::{get_running_client, CLIENT_PROCESS_NAME, GAME_PROCESS_NAME};
+    use super::{
+        get_running_client, ErrorKind, LockFile, Protocol, CLIENT_PROCESS_NAME, GAME_PROCESS_NAME,
+    };
     use sysinfo::{ProcessRefreshKind, RefreshKind, System};
 
+    #[test]
+    fn test_lock_file_parse() {
+        let lock_file =
+            LockFile::parse("LeagueClient:21296:51617:7dVzUj4rYSoQjbdXwT0-Kw:https\n").unwrap();
+
+        assert_eq!(lock_file.name(), "LeagueClient");
+        assert_eq!(lock_file.pid(), 21296);
+        assert_eq!(lock_file.port(), 51617);
+        assert_eq!(lock_file.password(), "7dVzUj4rYSoQjbdXwT0-Kw");
+        assert_eq!(lock_file.protocol(), Protocol::Https);
+
+        let truncated = LockFile::parse("LeagueClient:21296:51617").unwrap_err();
+        assert_eq!(truncated.kind(), ErrorKind::LockFileTruncated);
+
+        let truncated = LockFile::parse("").unwrap_err();
+        assert_eq!(truncated.kind(), ErrorKind::LockFileTruncated);
+
+        let malformed = LockFile::parse("LeagueClient:21296:port:password:https").unwrap_err();
+        assert_eq!(malformed.kind(), ErrorKind::LockFileMalformed);
+
+        let malformed = LockFile::parse("LeagueClient:21296:51617:password:ftp").unwrap_err();
+        assert_eq!(malformed.kind(), ErrorKind::LockFileMalformed);
+    }
+
     #[ignore = "This is only needed for testing, and doesn't need to be run all the time"]
     #[test]
     fn test_process_info() {
@@ -321,4 +618,44 @@ mod tests {
 
         println!("{process:?}");
     }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn test_wine_paths() {
+        use super::wine::{file_name, to_unix_path};
+        use std::path::Path;
+
+        let exe = r"C:\Riot Games\League of Legends\LeagueClientUx.exe";
+
+        assert_eq!(file_name(exe), "LeagueClientUx.exe");
+        assert_eq!(file_name("/usr/bin/wine64-preloader"), "wine64-preloader");
+
+        let path = to_unix_path(Path::new("/home/user/.wine"), exe).unwrap();
+        assert_eq!(
+            path,
+            Path::new(
+                "/home/user/.wine/dosdevices/c:/Riot Games/League of Legends/LeagueClientUx.exe"
+            )
+        );
+
+        assert!(to_unix_path(Path::new("/home/user/.wine"), "/usr/bin/wine").is_none());
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn test_wine_process_name() {
+        use super::{has_name, CLIENT_PROCESS_NAME, GAME_PROCESS_NAME};
+
+        // The kernel truncates the name to 15 bytes, so only the command line has the full name
+        let cmd = [
+            r"C:\Riot Games\League of Legends\LeagueClientUx.exe".to_string(),
+            "--app-port=1234".to_string(),
+        ];
+        assert!(has_name("LeagueClientUx.", &cmd, CLIENT_PROCESS_NAME));
+        assert!(!has_name("LeagueClientUx.", &cmd, GAME_PROCESS_NAME));
+
+        // A native process, or one without a command line, still matches by name
+        assert!(has_name("LeagueClientUx.exe", &[], CLIENT_PROCESS_NAME));
+        assert!(!has_name("wineserver", &[], CLIENT_PROCESS_NAME));
+    }
 }
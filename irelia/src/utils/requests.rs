@@ -3,7 +3,7 @@ use crate::Error;
 use http_body_util::{BodyExt, Full};
 use hyper::body::{Buf, Bytes, Incoming};
 use hyper::header::{ACCEPT, AUTHORIZATION, CONTENT_TYPE};
-use hyper::{Request, Response, Uri};
+use hyper::{Request, Response, StatusCode, Uri};
 use hyper_rustls::HttpsConnector;
 use hyper_util::client::legacy::connect::HttpConnector;
 use hyper_util::client::legacy::Client;
@@ -33,10 +33,15 @@ impl RequestClient {
     /// Creates a client to be passed to the LCU and in game structs
     pub fn new() -> RequestClient {
         // Get a client config using the riotgames.pem file
-        let tls = connector();
+        Self::with_tls_config(connector().clone())
+    }
+
+    #[must_use]
+    /// Creates a client which uses a custom TLS config, rather than only trusting the Riot certificate
+    pub fn with_tls_config(tls: rustls::ClientConfig) -> RequestClient {
         // Set up an HTTPS only client, with just the client config
         let https = hyper_rustls::HttpsConnectorBuilder::new()
-            .with_tls_config(tls.clone())
+            .with_tls_config(tls)
             .https_only()
             .enable_http1()
             .build();
@@ -50,6 +55,10 @@ impl RequestClient {
     ///
     /// # Errors
     /// if the body is invalid JSON, otherwise in any way hyper would normally
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(level = "debug", skip(self, body, auth_header, format))
+    )]
     pub(crate) async fn raw_request_template<T>(
         &self,
         url: &str,
@@ -81,7 +90,7 @@ impl RequestClient {
         // Add the auth header, if provided
         if let Some(header) = auth_header {
             builder = builder.header(AUTHORIZATION, header);
-        };
+        }
 
         let mut buffer = Full::default();
 
@@ -100,11 +109,26 @@ impl RequestClient {
         // Add the body to finalize
         let request = builder.body(buffer)?;
 
+        #[cfg(feature = "tracing")]
+        let start = std::time::Instant::now();
+
+        let response = self.client.request(request).await;
+
+        #[cfg(feature = "tracing")]
+        match &response {
+            Ok(response) => tracing::debug!(
+                status = response.status().as_u16(),
+                latency = ?start.elapsed(),
+                "request completed"
+            ),
+            Err(e) => tracing::warn!(error = %e, latency = ?start.elapsed(), "request failed"),
+        }
+
         // Return the incoming request
-        Ok(self.client.request(request).await?)
+        Ok(response?)
     }
 
-    /// Makes a request, collects the bytes, and returns the buf
+    /// Makes a request, collects the bytes, and returns the status alongside the buf
     pub(crate) async fn request_template<T>(
         &self,
         url: &str,
@@ -113,7 +137,7 @@ impl RequestClient {
         body: Option<T>,
         auth_header: Option<&str>,
         format: SerializeFormat,
-    ) -> Result<impl Buf + Sized, Error>
+    ) -> Result<(StatusCode, impl Buf + Sized), Error>
     where
         T: Serialize,
     {
@@ -121,12 +145,81 @@ impl RequestClient {
             .raw_request_template(url, endpoint, method, body, auth_header, format)
             .await?;
 
+        let status = response.status();
         let body = response.collect().await?;
 
-        Ok(body.aggregate())
+        Ok((status, body.aggregate()))
     }
 }
 
+/// The body the LCU, in game, and replay APIs send alongside most errors
+#[derive(serde_derive::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct ErrorBody {
+    pub(crate) error_code: String,
+    pub(crate) http_status: u16,
+    pub(crate) message: String,
+}
+
+impl ErrorBody {
+    /// Attempts to parse the body as JSON, and then as `MsgPack`,
+    /// returning `None` if it is not an error body
+    pub(crate) fn parse(body: &[u8]) -> Option<ErrorBody> {
+        serde_json::from_slice(body)
+            .ok()
+            .or_else(|| rmp_serde::from_slice(body).ok())
+    }
+
+    /// Turns the body into the error variant of the API it came from
+    pub(crate) fn into_error(self, api: Api, status: StatusCode) -> Error {
+        let ErrorBody {
+            error_code,
+            http_status,
+            message,
+        } = self;
+
+        match api {
+            #[cfg(feature = "rest")]
+            Api::Lcu => Error::Lcu {
+                status,
+                error_code,
+                message,
+                http_status,
+            },
+            #[cfg(feature = "in_game")]
+            Api::Game => Error::Game {
+                status,
+                error_code,
+                message,
+                http_status,
+            },
+        }
+    }
+}
+
+/// The APIs that send `ErrorBody`, each with its own variant of `Error`
+#[derive(Clone, Copy)]
+pub(crate) enum Api {
+    #[cfg(feature = "rest")]
+    Lcu,
+    #[cfg(feature = "in_game")]
+    Game,
+}
+
+/// Turns an error response into `Error::Lcu` or `Error::Game`
+///
+/// If the body is not in the standard format, `error_code` is empty,
+/// `message` holds the raw body, and `http_status` matches `status`
+pub(crate) fn api_error(api: Api, status: StatusCode, body: &[u8]) -> Error {
+    let error = ErrorBody::parse(body).unwrap_or_else(|| ErrorBody {
+        error_code: String::new(),
+        http_status: status.as_u16(),
+        message: String::from_utf8_lossy(body).into_owned(),
+    });
+
+    error.into_error(api, status)
+}
+
 // This isn't actually dead, just only when the replay API is not in use
 #[allow(dead_code)]
 #[repr(u8)]
@@ -145,7 +238,7 @@ impl SerializeFormat {
         }
     }
 
-    fn is_json(&self) -> bool {
+    pub(crate) fn is_json(&self) -> bool {
         match &self {
             SerializeFormat::Json => true,
             SerializeFormat::MsgPack => false,
@@ -1,37 +1,91 @@
 //! Module containing all the data on the websocket LCU bindings
 
+#[cfg(feature = "rest")]
+pub mod cache;
+pub mod call;
+pub mod filter;
+pub mod record;
+pub mod stream;
 pub mod types;
 mod utils;
 
 use std::fmt::{Display, Formatter};
-use std::net::TcpStream;
-use std::sync::mpsc::{Receiver, Sender};
+use std::marker::PhantomData;
+use std::net::{SocketAddr, TcpStream};
+use std::sync::mpsc::{Receiver, Sender, TryRecvError};
+use std::sync::Mutex;
 use std::thread::JoinHandle;
-use std::time::Duration;
+use std::time::{Duration, Instant};
 use std::{ops::ControlFlow, sync::Arc, thread};
 
-use rustls::pki_types::ServerName;
-use rustls::{ClientConfig, ClientConnection, StreamOwned};
+use rustls::ClientConfig;
+use serde::de::DeserializeOwned;
+use serde_json::Value;
+use tungstenite::handshake::client::Request;
+use tungstenite::handshake::HandshakeError;
 use tungstenite::stream::MaybeTlsStream;
 use tungstenite::util::NonBlockingResult;
 use tungstenite::{client::IntoClientRequest, http::HeaderValue, Connector, Message, WebSocket};
 
-use crate::utils::process_info::{CLIENT_PROCESS_NAME, GAME_PROCESS_NAME};
+use crate::utils::credentials::{CredentialSource, RunningClient};
+use crate::ws::call::{call_frame, CallError, CallResult, PendingCalls};
+use crate::ws::filter::{Filtered, UriFilter};
 use crate::ws::types::{Event, EventKind, RequestType};
 use crate::ws::utils::EventMap;
-use crate::{
-    process_info,
-    utils::{process_info::get_running_client, setup_tls::connector},
-    Error,
-};
+use crate::{process_info, utils::setup_tls::connector, Error};
 
-type WebSocketStream = WebSocket<MaybeTlsStream<StreamOwned<ClientConnection, TcpStream>>>;
+type WebSocketStream = WebSocket<MaybeTlsStream<TcpStream>>;
+
+/// How long `LCUWebSocket::call` waits for a reply by default
+const DEFAULT_CALL_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// How long a read on the socket blocks for before new commands are checked for,
+/// this is also the delay between failed connection attempts
+const POLL_INTERVAL: Duration = Duration::from_millis(50);
 
 /// Struct representing a connection to the LCU websocket
 pub struct LCUWebSocket {
     ws_sender: Sender<ChannelMessage>,
-    handle: JoinHandle<()>,
-    id_free_list: EventMap<(usize, Vec<usize>)>,
+    /// Only taken by `join`, so the event loop is closed on drop otherwise
+    handle: Option<JoinHandle<()>>,
+    id_free_list: IdFreeList,
+}
+
+/// Shared with every `Subscription`, so ids can be returned when they are dropped
+type IdFreeList = Arc<Mutex<EventMap<Ids>>>;
+
+/// The subscriber ids of one event kind
+#[derive(Default)]
+struct Ids {
+    next: usize,
+    /// Freed ids, which are handed out again before new ones
+    returned: Vec<usize>,
+    /// The ids owned by a live `Subscription`, along with the token of that guard,
+    /// so a guard whose id was freed by hand and handed out again leaves it alone
+    guarded: Vec<(usize, u64)>,
+    next_token: u64,
+}
+
+impl Ids {
+    fn take(&mut self) -> usize {
+        self.returned.pop().unwrap_or_else(|| {
+            let id = self.next;
+            self.next += 1;
+            id
+        })
+    }
+
+    /// Returns the id to the free list, or false if it isn't currently handed out
+    fn free(&mut self, id: usize) -> bool {
+        if id >= self.next || self.returned.contains(&id) {
+            return false;
+        }
+
+        self.guarded.retain(|&(guarded, _)| guarded != id);
+        self.returned.push(id);
+
+        true
+    }
 }
 
 #[derive(PartialEq)]
@@ -40,43 +94,226 @@ pub enum Flow {
     Continue,
 }
 
-pub trait Subscriber: Send + Sync {
+/// Changes to the state of the connection, passed to every subscriber
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConnectionEvent {
+    /// The connection was lost, or a reconnect was requested
+    Disconnected,
+    /// The connection was re-established, and every subscription was sent again
+    Reconnected,
+}
+
+pub trait Subscriber: Send {
     fn on_event(&mut self, event: &Event) -> ControlFlow<(), Flow>;
+
+    /// Called when the connection is lost, and again once it has been re-established,
+    /// by default this does nothing
+    fn on_connection_event(&mut self, event: ConnectionEvent) {
+        let _ = event;
+    }
+}
+
+impl<F> Subscriber for F
+where
+    F: FnMut(&Event) -> ControlFlow<(), Flow> + Send,
+{
+    fn on_event(&mut self, event: &Event) -> ControlFlow<(), Flow> {
+        self(event)
+    }
+}
+
+/// A subscriber which receives the payload of each event already decoded
+pub trait TypedSubscriber<T>: Send {
+    fn on_event(&mut self, event: &Event, data: T) -> ControlFlow<(), Flow>;
+
+    /// Called when the payload does not match `T`, by default the event is skipped
+    fn on_decode_error(
+        &mut self,
+        event: &Event,
+        error: serde_json::Error,
+    ) -> ControlFlow<(), Flow> {
+        let _ = (event, error);
+        ControlFlow::Continue(Flow::Continue)
+    }
+
+    /// Same as `Subscriber::on_connection_event`
+    fn on_connection_event(&mut self, event: ConnectionEvent) {
+        let _ = event;
+    }
+}
+
+impl<T, F> TypedSubscriber<T> for F
+where
+    F: FnMut(&Event, T) -> ControlFlow<(), Flow> + Send,
+{
+    fn on_event(&mut self, event: &Event, data: T) -> ControlFlow<(), Flow> {
+        self(event, data)
+    }
+}
+
+/// Adapts a `TypedSubscriber` into a `Subscriber`, decoding the payload of every event
+pub struct Typed<T, S> {
+    subscriber: S,
+    // `fn() -> T` keeps this `Send` and `Sync` regardless of `T`
+    _data: PhantomData<fn() -> T>,
+}
+
+impl<T, S> Typed<T, S> {
+    #[must_use]
+    pub fn new(subscriber: S) -> Self {
+        Self {
+            subscriber,
+            _data: PhantomData,
+        }
+    }
+}
+
+impl<T: DeserializeOwned, S: TypedSubscriber<T>> Subscriber for Typed<T, S> {
+    fn on_event(&mut self, event: &Event) -> ControlFlow<(), Flow> {
+        match event.2.data_as::<T>() {
+            Ok(data) => self.subscriber.on_event(event, data),
+            Err(e) => self.subscriber.on_decode_error(event, e),
+        }
+    }
+
+    fn on_connection_event(&mut self, event: ConnectionEvent) {
+        self.subscriber.on_connection_event(event);
+    }
 }
 
-pub trait ErrorHandler: Send + Sync {
+pub trait ErrorHandler: Send {
     fn on_error(&mut self, error: WebsocketError) -> ControlFlow<(), Flow>;
 }
 
-/// This is a zero sized struct which calls `eprintln!()` and then breaks on error
+impl<F> ErrorHandler for F
+where
+    F: FnMut(WebsocketError) -> ControlFlow<(), Flow> + Send,
+{
+    fn on_error(&mut self, error: WebsocketError) -> ControlFlow<(), Flow> {
+        self(error)
+    }
+}
+
+/// This is a zero sized struct which breaks on error,
+/// logging it first if the `tracing` feature is enabled
 pub struct DefaultErrorHandler;
 
 impl ErrorHandler for DefaultErrorHandler {
     fn on_error(&mut self, error: WebsocketError) -> ControlFlow<(), Flow> {
-        eprintln!("{error}");
+        #[cfg(feature = "tracing")]
+        tracing::error!(%error, "websocket error, closing the connection");
+        #[cfg(not(feature = "tracing"))]
+        let _ = error;
+
         ControlFlow::Break(())
     }
 }
 
 enum ChannelMessage {
-    Subscribe(RequestType, EventKind, Box<dyn Subscriber>),
+    Subscribe(RequestType, EventKind, SubscriberID, Box<dyn Subscriber>),
     Unsubscribe(SubscriberID, EventKind),
+    Call(String, Vec<Value>, Instant, Sender<CallResult>),
+    Close,
 }
 
 #[derive(Clone, Copy)]
 #[repr(transparent)]
 pub struct SubscriberID(usize);
 
+/// A subscription which is removed when dropped
+pub struct Subscription {
+    ws_sender: Sender<ChannelMessage>,
+    id_free_list: IdFreeList,
+    event_kind: EventKind,
+    id: SubscriberID,
+    token: u64,
+    active: bool,
+}
+
+impl Subscription {
+    #[must_use]
+    pub fn id(&self) -> SubscriberID {
+        self.id
+    }
+
+    #[must_use]
+    pub fn event_kind(&self) -> &EventKind {
+        &self.event_kind
+    }
+
+    #[must_use]
+    /// Keeps the subscriber around after the guard is gone, returning the id
+    /// so it can still be passed to `LCUWebSocket::unsubscribe`
+    pub fn detach(mut self) -> SubscriberID {
+        self.active = false;
+
+        let mut id_free_list = self
+            .id_free_list
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner);
+
+        let token = (self.id.0, self.token);
+        id_free_list
+            .get_mut(&self.event_kind)
+            .guarded
+            .retain(|&guarded| guarded != token);
+
+        self.id
+    }
+}
+
+impl Drop for Subscription {
+    fn drop(&mut self) {
+        if !self.active {
+            return;
+        }
+
+        let mut id_free_list = self
+            .id_free_list
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner);
+
+        // The id was already unsubscribed by hand, and may belong to another subscriber by now
+        if !id_free_list
+            .get_mut(&self.event_kind)
+            .guarded
+            .contains(&(self.id.0, self.token))
+        {
+            return;
+        }
+
+        // If the connection has already been closed, there is nothing to remove
+        let _ = unsubscribe(
+            &self.ws_sender,
+            &mut id_free_list,
+            self.event_kind.clone(),
+            self.id,
+        );
+    }
+}
+
+fn unsubscribe(
+    ws_sender: &Sender<ChannelMessage>,
+    id_free_list: &mut EventMap<Ids>,
+    event_kind: EventKind,
+    id: SubscriberID,
+) -> Option<()> {
+    // Freeing an id twice would hand it out to two subscribers
+    if !id_free_list.get_mut(&event_kind).free(id.0) {
+        return Some(());
+    }
+
+    ws_sender
+        .send(ChannelMessage::Unsubscribe(id, event_kind))
+        .ok()
+}
+
 impl LCUWebSocket {
     /// Creates a new connection to the LCU websocket using the default error handler
     ///
     /// # Errors
     /// This function will return an error if the LCU is not running,
     /// or if it cannot connect to the websocket
-    ///
-    /// # Panics
-    ///
-    /// If the auth header returned is somehow invalid (though I have not seen this in practice)
     pub fn new() -> Result<Self, Error> {
         Self::new_with_error_handler(DefaultErrorHandler)
     }
@@ -86,29 +323,50 @@ impl LCUWebSocket {
     /// # Errors
     /// This function will return an error if the LCU is not running,
     /// or if it cannot connect to the websocket
+    pub fn new_with_error_handler(
+        error_handler: impl ErrorHandler + 'static,
+    ) -> Result<Self, Error> {
+        Self::new_with_source(RunningClient::default(), error_handler)
+    }
+
+    /// Creates a new connection to the LCU websocket, using the credentials from the given source
     ///
-    /// # Panics
+    /// The source is queried again every time the websocket reconnects
     ///
-    /// If the auth header returned is somehow invalid (though I have not seen this in practice)
-    pub fn new_with_error_handler(
+    /// # Errors
+    /// This function will return an error if the LCU is not running,
+    /// or if it cannot connect to the websocket
+    pub fn new_with_source(
+        source: impl CredentialSource + 'static,
         error_handler: impl ErrorHandler + 'static,
     ) -> Result<Self, Error> {
-        let tls = connector();
-        let tls = Arc::new(tls.clone());
+        Self::new_with_tls_config(source, error_handler, Arc::new(connector().clone()))
+    }
 
+    /// Same as `new_with_source`, but with a custom TLS config,
+    /// rather than only trusting the Riot certificate
+    ///
+    /// # Errors
+    /// This function will return an error if the LCU is not running,
+    /// or if it cannot connect to the websocket
+    pub fn new_with_tls_config(
+        source: impl CredentialSource + 'static,
+        error_handler: impl ErrorHandler + 'static,
+        tls: Arc<ClientConfig>,
+    ) -> Result<Self, Error> {
         let (ws_sender, ws_receiver) = std::sync::mpsc::channel::<ChannelMessage>();
 
         let handle = thread::spawn(move || {
             let ws_receiver = ws_receiver;
             let tls = tls;
 
-            event_loop(error_handler, &ws_receiver, &tls);
+            event_loop(error_handler, &ws_receiver, &tls, &source);
         });
 
         Ok(Self {
             ws_sender,
-            handle,
-            id_free_list: EventMap::new(),
+            handle: Some(handle),
+            id_free_list: Arc::new(Mutex::new(EventMap::new())),
         })
     }
 
@@ -120,51 +378,256 @@ impl LCUWebSocket {
         event_kind: EventKind,
         subscriber: impl Subscriber + 'static,
     ) -> Option<SubscriberID> {
-        let (next_id, returned) = self.id_free_list.get_mut(&event_kind);
+        let mut id_free_list = self
+            .id_free_list
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner);
 
-        self.ws_sender
-            .send(ChannelMessage::Subscribe(
-                RequestType::Subscribe,
-                event_kind,
-                Box::new(subscriber),
-            ))
-            .ok()?;
+        let ids = id_free_list.get_mut(&event_kind);
 
-        let id = if returned.is_empty() {
-            let tmp = *next_id;
-            *next_id += 1;
-            tmp
-        } else {
-            returned.remove(0)
-        };
+        let id = ids.take();
+
+        let sent = self.ws_sender.send(ChannelMessage::Subscribe(
+            RequestType::Subscribe,
+            event_kind,
+            SubscriberID(id),
+            Box::new(subscriber),
+        ));
+
+        if sent.is_err() {
+            ids.returned.push(id);
+            return None;
+        }
 
         Some(SubscriberID(id))
     }
 
+    /// Same as `subscribe`, but the subscriber is removed once the returned guard is dropped
+    ///
+    /// Returns `None` is the websocket connection has already been closed previously
+    pub fn subscribe_guarded(
+        &mut self,
+        event_kind: EventKind,
+        subscriber: impl Subscriber + 'static,
+    ) -> Option<Subscription> {
+        let id = self.subscribe(event_kind.clone(), subscriber)?;
+
+        let token = {
+            let mut id_free_list = self
+                .id_free_list
+                .lock()
+                .unwrap_or_else(std::sync::PoisonError::into_inner);
+
+            let ids = id_free_list.get_mut(&event_kind);
+            let token = ids.next_token;
+            ids.next_token += 1;
+            ids.guarded.push((id.0, token));
+
+            token
+        };
+
+        Some(Subscription {
+            ws_sender: self.ws_sender.clone(),
+            id_free_list: self.id_free_list.clone(),
+            event_kind,
+            id,
+            token,
+            active: true,
+        })
+    }
+
+    /// Subscribes to `OnJsonApiEvent`, only passing events that match the filter to the subscriber
+    ///
+    /// Every filtered subscriber shares the same subscription on the LCU side,
+    /// unsubscribe using `EventKind::JsonApiEvent` and the returned id
+    ///
+    /// Returns `None` is the websocket connection has already been closed previously
+    pub fn subscribe_filtered(
+        &mut self,
+        filter: UriFilter,
+        subscriber: impl Subscriber + 'static,
+    ) -> Option<SubscriberID> {
+        self.subscribe(EventKind::JsonApiEvent, Filtered::new(filter, subscriber))
+    }
+
+    /// Same as `subscribe_filtered`, but the payload of each event is decoded into `T`
+    ///
+    /// Returns `None` is the websocket connection has already been closed previously
+    pub fn subscribe_typed<T: DeserializeOwned + 'static>(
+        &mut self,
+        filter: UriFilter,
+        subscriber: impl TypedSubscriber<T> + 'static,
+    ) -> Option<SubscriberID> {
+        self.subscribe_filtered(filter, Typed::new(subscriber))
+    }
+
     /// Unsubscribe to a new API event
     ///
     /// If all subscribers have been removed, this will unsubscribe from the event as a whole
     ///
+    /// Unsubscribing an id that isn't subscribed does nothing, and a `Subscription`
+    /// whose id is unsubscribed this way no longer removes anything when dropped
+    ///
     /// Returns `None` if the connection to the websocket was already closed
     pub fn unsubscribe(&mut self, event_kind: EventKind, id: SubscriberID) -> Option<()> {
-        let (_, returned) = self.id_free_list.get_mut(&event_kind);
+        let mut id_free_list = self
+            .id_free_list
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner);
 
-        returned.push(id.0);
+        unsubscribe(&self.ws_sender, &mut id_free_list, event_kind, id)
+    }
+
+    /// Calls a procedure on the LCU over the open socket, rather than a new HTTPS connection,
+    /// giving up after ten seconds
+    ///
+    /// The result is sent to the returned receiver once the LCU replies, calls made while
+    /// disconnected are sent once the connection is back, unless they time out first
+    ///
+    /// Returns `None` if the connection to the websocket was already closed
+    pub fn call(&self, uri: impl Into<String>, args: Vec<Value>) -> Option<Receiver<CallResult>> {
+        self.call_with_timeout(uri, args, DEFAULT_CALL_TIMEOUT)
+    }
+
+    /// Same as `call`, but with a custom timeout
+    ///
+    /// Returns `None` if the connection to the websocket was already closed
+    pub fn call_with_timeout(
+        &self,
+        uri: impl Into<String>,
+        args: Vec<Value>,
+        timeout: Duration,
+    ) -> Option<Receiver<CallResult>> {
+        let (sender, receiver) = std::sync::mpsc::channel();
+        // Time spent waiting for a connection counts towards the timeout
+        let deadline = Instant::now() + timeout;
 
         self.ws_sender
-            .send(ChannelMessage::Unsubscribe(id, event_kind))
-            .ok()
+            .send(ChannelMessage::Call(uri.into(), args, deadline, sender))
+            .ok()?;
+
+        Some(receiver)
+    }
+
+    #[must_use]
+    /// Sends a close frame, ends the event loop, and waits for the thread to exit
+    ///
+    /// Commands sent before this, such as unsubscribing, are handled first
+    ///
+    /// Returns `None` if the event loop panicked
+    pub fn close(self) -> Option<()> {
+        // If the event loop has already ended, there is nothing to close
+        let _ = self.ws_sender.send(ChannelMessage::Close);
+
+        self.join()
     }
 
     #[must_use]
-    /// Terminate the event loop
-    pub fn join(self) -> Option<()> {
-        self.handle.join().ok()
+    /// Waits for the event loop to end, which only happens once it is closed,
+    /// or a handler returns `ControlFlow::Break`
+    pub fn join(mut self) -> Option<()> {
+        self.handle.take()?.join().ok()
     }
 
     #[must_use]
     pub fn is_finished(&self) -> bool {
-        self.handle.is_finished()
+        self.handle.as_ref().is_none_or(JoinHandle::is_finished)
+    }
+}
+
+impl Drop for LCUWebSocket {
+    /// Closes the connection without waiting for the event loop, which would otherwise
+    /// keep running for as long as any `Subscription` is alive
+    fn drop(&mut self) {
+        if self.handle.is_some() {
+            let _ = self.ws_sender.send(ChannelMessage::Close);
+        }
+    }
+}
+
+type SubscriberMap = EventMap<Vec<Option<Box<dyn Subscriber>>>>;
+
+/// Everything waiting on the connection, which is kept across reconnects
+#[derive(Default)]
+struct Listeners {
+    subscribers: SubscriberMap,
+    calls: PendingCalls,
+}
+
+impl Listeners {
+    /// Stores the subscriber, returning true if it is the first one for its event kind
+    fn add(
+        &mut self,
+        event_kind: &EventKind,
+        id: SubscriberID,
+        subscriber: Box<dyn Subscriber>,
+    ) -> bool {
+        let kind_subscribers = self.subscribers.get_mut(event_kind);
+
+        let first = kind_subscribers.iter().flatten().count() == 0;
+
+        // Ids are handed out by `LCUWebSocket`, so the slot always matches
+        if kind_subscribers.len() <= id.0 {
+            kind_subscribers.resize_with(id.0 + 1, || None);
+        }
+
+        kind_subscribers[id.0] = Some(subscriber);
+
+        first
+    }
+
+    /// Removes the subscriber, returning true if there are none left for its event kind
+    fn remove(&mut self, event_kind: &EventKind, id: SubscriberID) -> bool {
+        let kind_subscribers = self.subscribers.get_mut(event_kind);
+
+        if let Some(subscriber) = kind_subscribers.get_mut(id.0) {
+            *subscriber = None;
+        }
+
+        kind_subscribers.iter().flatten().count() == 0
+    }
+
+    /// Handles the commands sent while there is no connection, returning false once closed
+    ///
+    /// Subscriptions and calls are sent once connected, and calls whose timeout passes
+    /// before then fail with `CallError::Timeout`
+    fn drain_disconnected(&mut self, ws_receiver: &Receiver<ChannelMessage>) -> bool {
+        while let Some(message) = try_next(ws_receiver) {
+            match message {
+                ChannelMessage::Subscribe(_, event_kind, id, subscriber) => {
+                    self.add(&event_kind, id, subscriber);
+                }
+                ChannelMessage::Unsubscribe(id, event_kind) => {
+                    self.remove(&event_kind, id);
+                }
+                ChannelMessage::Call(uri, args, deadline, sender) => {
+                    self.calls.queue(uri, args, deadline, sender);
+                }
+                ChannelMessage::Close => return false,
+            }
+        }
+
+        self.calls.expire(Instant::now());
+
+        true
+    }
+
+    fn notify(&mut self, event: ConnectionEvent) {
+        for (_, kind_subscribers) in self.subscribers.iter_mut() {
+            for subscriber in kind_subscribers.iter_mut().flatten() {
+                subscriber.on_connection_event(event);
+            }
+        }
+    }
+}
+
+/// Takes the next pending command, if there is one
+fn try_next(ws_receiver: &Receiver<ChannelMessage>) -> Option<ChannelMessage> {
+    match ws_receiver.try_recv() {
+        Ok(message) => Some(message),
+        Err(TryRecvError::Empty) => None,
+        // Every sender is gone, so nothing could ever close the connection
+        Err(TryRecvError::Disconnected) => Some(ChannelMessage::Close),
     }
 }
 
@@ -172,105 +635,101 @@ fn event_loop(
     mut error_handler: impl ErrorHandler,
     ws_receiver: &Receiver<ChannelMessage>,
     tls: &Arc<ClientConfig>,
+    source: &dyn CredentialSource,
 ) {
-    type SubscriberMap = EventMap<Vec<Option<Box<dyn Subscriber>>>>;
-
     let mut maybe_stream = None;
 
-    let mut subscribers: SubscriberMap = SubscriberMap::new();
+    let mut listeners = Listeners::default();
     let error_handler: &mut dyn ErrorHandler = &mut error_handler;
 
     'outer: loop {
         if let Some(stream) = &mut maybe_stream {
-            if let Ok(message) = ws_receiver.try_recv() {
+            // Drain every pending command, as each read below can block for up to the poll interval
+            while let Some(message) = try_next(ws_receiver) {
                 match message {
-                    ChannelMessage::Subscribe(code, event_kind, subscriber) => {
-                        let subscribers = subscribers.get_mut(&event_kind);
-
-                        if subscribers.is_empty() {
+                    ChannelMessage::Subscribe(code, event_kind, id, subscriber) => {
+                        // The subscriber is stored first, so that if sending fails and the
+                        // connection is re-established, the command is replayed with the rest
+                        if listeners.add(&event_kind, id, subscriber) {
                             let endpoint_str = event_kind.to_string();
 
-                            let command = format!("[{}, \"{endpoint_str}\"]", code.clone() as u8);
+                            let command = format!("[{}, \"{endpoint_str}\"]", code.code());
 
-                            println!("{command}");
+                            #[cfg(feature = "tracing")]
+                            tracing::debug!(event = %endpoint_str, "subscribing");
 
-                            let continues = send_command(error_handler, stream, tls, command);
+                            #[rustfmt::skip]
+                            let continues = send_command(error_handler, stream, tls, source, &mut listeners, ws_receiver, command);
                             if !continues {
                                 break 'outer;
                             }
                         }
-
-                        let mut idx = subscribers.len();
-
-                        for (first_none_idx, maybe_subscriber) in subscribers.iter_mut().enumerate()
-                        {
-                            if maybe_subscriber.is_none() {
-                                idx = first_none_idx;
-                                break;
-                            }
-                        }
-
-                        if idx == subscribers.len() {
-                            subscribers.push(Some(subscriber));
-                        } else {
-                            subscribers[idx] = Some(subscriber);
-                        }
                     }
                     ChannelMessage::Unsubscribe(subscriber_id, event_kind) => {
-                        let subscribers = subscribers.get_mut(&event_kind);
+                        if listeners.remove(&event_kind, subscriber_id) {
+                            #[cfg(feature = "tracing")]
+                            tracing::debug!(event = %event_kind.to_string(), "unsubscribing");
 
-                        if subscribers.iter().flatten().count() == 0 {
                             let unsub = format!(
                                 "[{}, \"{}\"]",
-                                RequestType::Unsubscribe as u8,
+                                RequestType::Unsubscribe.code(),
                                 event_kind.to_string()
                             );
-                            let continues = send_command(error_handler, stream, tls, unsub);
+                            #[rustfmt::skip]
+                            let continues = send_command(error_handler, stream, tls, source, &mut listeners, ws_receiver, unsub);
                             if !continues {
-                                break;
+                                break 'outer;
                             }
                         }
+                    }
+                    ChannelMessage::Call(uri, args, deadline, sender) => {
+                        let id = listeners.calls.insert(deadline, sender);
+                        let command = call_frame(id, uri, args);
+
+                        #[rustfmt::skip]
+                        let continues = send_command(error_handler, stream, tls, source, &mut listeners, ws_receiver, command);
+                        if !continues {
+                            break 'outer;
+                        }
+                    }
+                    ChannelMessage::Close => {
+                        // The connection is going away regardless, so errors are ignored
+                        let _ = stream.close(None);
+                        let _ = stream.flush();
 
-                        subscribers[subscriber_id.0] = None;
+                        break 'outer;
                     }
                 }
-            };
-
-            let read = stream.read();
-
-            if let Ok(Some(message)) = read.no_block() {
-                let data = message.into_data();
-                if !data.is_empty() {
-                    let maybe_json = serde_json::from_slice::<Event>(&data);
-
-                    match maybe_json {
-                        Ok(json) => {
-                            let subscribers = subscribers.get_mut(&json.1);
-
-                            for subscriber in subscribers.iter_mut().flatten() {
-                                let mut control = subscriber.on_event(&json);
-                                #[rustfmt::skip]
-                                    let continues = budget_recursive(&mut control, tls, stream, error_handler);
-                                if !continues {
-                                    break 'outer;
-                                }
-                            }
-                        }
-                        Err(e) => {
-                            let mut control = error_handler.on_error(e.into());
+            }
 
-                            #[rustfmt::skip]
-                                let continues = budget_recursive(&mut control, tls, stream, error_handler);
+            listeners.calls.expire(Instant::now());
 
-                            if !continues {
-                                break;
-                            }
-                        }
-                    }
+            // Timeouts surface as `WouldBlock` on unix and `TimedOut` on windows,
+            // either way there is nothing to read yet
+            let mut control = match stream.read().no_block() {
+                // Pings are answered by tungstenite, and a close frame is followed by
+                // `ConnectionClosed` on the next read, so only data frames are dispatched
+                Ok(Some(message @ (Message::Text(_) | Message::Binary(_)))) => {
+                    dispatch(&message.into_data(), &mut listeners, error_handler)
+                }
+                Ok(_) => continue,
+                Err(tungstenite::Error::Io(e)) if e.kind() == std::io::ErrorKind::TimedOut => {
+                    continue
                 }
+                Err(e) => error_handler.on_error(e.into()),
+            };
+
+            #[rustfmt::skip]
+            let continues = budget_recursive(&mut control, tls, source, stream, error_handler, &mut listeners, ws_receiver);
+            if !continues {
+                break;
             }
         } else {
-            match connect(tls) {
+            if !listeners.drain_disconnected(ws_receiver) {
+                break;
+            }
+
+            match connect_and_resubscribe(tls, source, &mut listeners) {
                 Ok(stream) => maybe_stream = Some(stream),
                 Err(e) => {
                     let control = error_handler.on_error(e);
@@ -278,24 +737,79 @@ fn event_loop(
                     if control == ControlFlow::Break(()) {
                         break;
                     }
+
+                    thread::sleep(POLL_INTERVAL);
                 }
             }
         }
     }
+
+    // Dropping the senders would also end these calls, but this is clearer to the caller
+    listeners.calls.fail_all(&CallError::Disconnected);
+}
+
+/// Passes a message to every subscriber of its event kind
+///
+/// A subscriber asking to reconnect doesn't keep the event from the ones after it,
+/// the reconnect happens once all of them have seen it, while `ControlFlow::Break`
+/// stops at that subscriber, as the event loop is ending
+///
+/// Replies to calls are passed to the matching caller instead
+fn dispatch(
+    data: &[u8],
+    listeners: &mut Listeners,
+    error_handler: &mut dyn ErrorHandler,
+) -> ControlFlow<(), Flow> {
+    if data.is_empty() {
+        return ControlFlow::Continue(Flow::Continue);
+    }
+
+    let frame = match serde_json::from_slice::<Value>(data) {
+        Ok(frame) => frame,
+        Err(e) => return error_handler.on_error(e.into()),
+    };
+
+    if listeners.calls.resolve(&frame) {
+        return ControlFlow::Continue(Flow::Continue);
+    }
+
+    match serde_json::from_value::<Event>(frame) {
+        Ok(json) => {
+            let mut control = ControlFlow::Continue(Flow::Continue);
+
+            for subscriber in listeners.subscribers.get_mut(&json.1).iter_mut().flatten() {
+                match subscriber.on_event(&json) {
+                    ControlFlow::Break(()) => return ControlFlow::Break(()),
+                    ControlFlow::Continue(Flow::TryReconnect) => {
+                        control = ControlFlow::Continue(Flow::TryReconnect);
+                    }
+                    ControlFlow::Continue(Flow::Continue) => {}
+                }
+            }
+
+            control
+        }
+        Err(e) => error_handler.on_error(e.into()),
+    }
 }
 
 fn send_command(
     error_handler: &mut dyn ErrorHandler,
     stream: &mut WebSocketStream,
     tls: &Arc<ClientConfig>,
+    source: &dyn CredentialSource,
+    listeners: &mut Listeners,
+    ws_receiver: &Receiver<ChannelMessage>,
     command: String,
 ) -> bool {
     if let Err(e) = stream.send(Message::Text(command)) {
-        println!("{e}");
+        #[cfg(feature = "tracing")]
+        tracing::warn!(error = %e, "failed to send command");
+
         let mut control = error_handler.on_error(e.into());
 
         #[rustfmt::skip]
-        let continues = budget_recursive(&mut control, tls, stream, error_handler);
+        let continues = budget_recursive(&mut control, tls, source, stream, error_handler, listeners, ws_receiver);
 
         if !continues {
             return false;
@@ -308,21 +822,46 @@ fn send_command(
 fn budget_recursive(
     c: &mut ControlFlow<(), Flow>,
     tls: &Arc<ClientConfig>,
+    source: &dyn CredentialSource,
     stream: &mut WebSocketStream,
     f: &mut dyn ErrorHandler,
+    listeners: &mut Listeners,
+    ws_receiver: &Receiver<ChannelMessage>,
 ) -> bool {
+    if *c == ControlFlow::Continue(Flow::Continue) {
+        return true;
+    }
+
+    #[cfg(feature = "tracing")]
+    let _span = tracing::info_span!("reconnect").entered();
+
+    // Replies are tied to the connection, so they will never arrive on the new one
+    listeners.calls.fail_all(&CallError::Disconnected);
+    listeners.notify(ConnectionEvent::Disconnected);
+
     while *c != ControlFlow::Continue(Flow::Continue) {
         if *c == ControlFlow::Break(()) {
             return false;
         }
 
-        match connect(tls) {
+        match connect_and_resubscribe(tls, source, listeners) {
             Ok(new_stream) => {
+                #[cfg(feature = "tracing")]
+                tracing::info!("reconnected");
+
                 *stream = new_stream;
+                listeners.notify(ConnectionEvent::Reconnected);
                 break;
             }
             Err(e) => {
                 *c = f.on_error(e);
+
+                thread::sleep(POLL_INTERVAL);
+
+                // Commands keep arriving while reconnecting, and calls can time out meanwhile
+                if !listeners.drain_disconnected(ws_receiver) {
+                    return false;
+                }
             }
         }
     }
@@ -330,6 +869,38 @@ fn budget_recursive(
     true
 }
 
+/// Connects, then sends a subscribe command for every event kind with a live subscriber,
+/// as the LCU forgets every subscription when the connection is lost,
+/// followed by the calls made while disconnected
+fn connect_and_resubscribe(
+    tls: &Arc<ClientConfig>,
+    source: &dyn CredentialSource,
+    listeners: &mut Listeners,
+) -> Result<WebSocketStream, WebsocketError> {
+    let mut stream = connect(tls, source)?;
+
+    for (event_kind, kind_subscribers) in listeners.subscribers.iter_mut() {
+        if kind_subscribers.iter().flatten().count() != 0 {
+            #[cfg(feature = "tracing")]
+            tracing::debug!(event = %event_kind.to_string(), "resubscribing");
+
+            let command = format!(
+                "[{}, \"{}\"]",
+                RequestType::Subscribe.code(),
+                event_kind.to_string()
+            );
+
+            stream.send(Message::Text(command))?;
+        }
+    }
+
+    for command in listeners.calls.send_queued() {
+        stream.send(Message::Text(command))?;
+    }
+
+    Ok(stream)
+}
+
 #[derive(Debug)]
 pub enum WebsocketError {
     Tungstenite(tungstenite::Error),
@@ -377,47 +948,417 @@ impl From<process_info::Error> for WebsocketError {
     }
 }
 
-fn connect(tls: &Arc<ClientConfig>) -> Result<WebSocketStream, WebsocketError> {
+#[cfg_attr(
+    feature = "tracing",
+    tracing::instrument(level = "debug", skip_all, err(Display))
+)]
+fn connect(
+    tls: &Arc<ClientConfig>,
+    source: &dyn CredentialSource,
+) -> Result<WebSocketStream, WebsocketError> {
     const TIMEOUT: Duration = Duration::from_millis(100);
 
-    let (addr, auth) = get_running_client(CLIENT_PROCESS_NAME, GAME_PROCESS_NAME, false)?;
+    let (addr, auth) = source.credentials()?;
 
-    let str_req = format!("wss://{addr}");
+    let request = handshake_request(addr, &auth)?;
 
-    let auth_header = HeaderValue::from_str(&auth).unwrap();
-
-    let mut request = str_req.into_client_request()?;
-
-    request.headers_mut().insert("Authorization", auth_header);
+    #[cfg(feature = "tracing")]
+    tracing::debug!(%addr, "connecting");
 
     let tcp_stream = TcpStream::connect_timeout(&addr, TIMEOUT)?;
 
-    let addr = ServerName::IpAddress(addr.ip().into());
-
-    let client_connection = ClientConnection::new(tls.clone(), addr).unwrap();
-    let rustls_stream = StreamOwned::new(client_connection, tcp_stream);
-
     let (stream, _) = tungstenite::client_tls_with_config(
         request.clone(),
-        rustls_stream,
+        tcp_stream,
         None,
         Some(Connector::Rustls(tls.clone())),
     )
-    .expect("The TLS handshake should never fail");
+    .map_err(|e| match e {
+        HandshakeError::Failure(e) => WebsocketError::from(e),
+        // The socket is still blocking at this point, so the handshake can't be interrupted
+        HandshakeError::Interrupted(_) => {
+            std::io::Error::from(std::io::ErrorKind::WouldBlock).into()
+        }
+    })?;
 
     let MaybeTlsStream::Rustls(stream_inner) = stream.get_ref() else {
         unreachable!();
     };
 
-    stream_inner.sock.sock.set_nonblocking(true).unwrap();
+    // Reads block until a message arrives, or until the interval passes,
+    // so an idle connection doesn't spin while waiting for either
+    stream_inner.sock.set_read_timeout(Some(POLL_INTERVAL))?;
 
     Ok(stream)
 }
 
+/// Builds the request for the websocket handshake, including the auth header
+///
+/// A credential source can return any string, so an auth header that is not a valid
+/// header value, such as one containing a newline, is returned as an error
+fn handshake_request(addr: SocketAddr, auth: &str) -> Result<Request, tungstenite::Error> {
+    let str_req = format!("wss://{addr}");
+
+    let auth_header = HeaderValue::from_str(auth).map_err(tungstenite::http::Error::from)?;
+
+    let mut request = str_req.into_client_request()?;
+
+    request.headers_mut().insert("Authorization", auth_header);
+
+    Ok(request)
+}
+
 #[cfg(test)]
 mod test {
-    use crate::ws::types::Event;
+    #[cfg(feature = "mock")]
+    use super::{ConnectionEvent, Subscriber};
+    use super::{Flow, LCUWebSocket, WebsocketError};
+    use crate::process_info::{self, ErrorKind};
+    use crate::ws::types::{Event, EventKind, EventType, RequestType};
     use serde_json::json;
+    use std::ops::ControlFlow;
+
+    /// Passes on the payload of every event, and every change to the connection
+    #[cfg(feature = "mock")]
+    struct Recording {
+        data: std::sync::mpsc::Sender<serde_json::Value>,
+        connection: std::sync::mpsc::Sender<ConnectionEvent>,
+    }
+
+    #[cfg(feature = "mock")]
+    impl Subscriber for Recording {
+        fn on_event(&mut self, event: &Event) -> ControlFlow<(), Flow> {
+            let _ = self.data.send(event.2.data.clone());
+            ControlFlow::Continue(Flow::Continue)
+        }
+
+        fn on_connection_event(&mut self, event: ConnectionEvent) {
+            let _ = self.connection.send(event);
+        }
+    }
+
+    #[cfg(feature = "mock")]
+    async fn wait_for_subscription(server: &crate::mock::MockServer, event_name: &str) {
+        let deadline = std::time::Instant::now() + std::time::Duration::from_secs(5);
+
+        while !server.subscribed(event_name) {
+            assert!(
+                std::time::Instant::now() < deadline,
+                "the subscription never arrived"
+            );
+            tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+        }
+    }
+
+    fn ignore(_: &Event) -> ControlFlow<(), Flow> {
+        ControlFlow::Continue(Flow::Continue)
+    }
+
+    #[test]
+    fn test_subscription_guard_and_close() {
+        // The client never comes up, so the loop keeps retrying until it is closed
+        let source = || {
+            Err(process_info::Error::new(
+                ErrorKind::NotRunning,
+                "not running",
+            ))
+        };
+        let retry = |_: WebsocketError| ControlFlow::Continue(Flow::TryReconnect);
+        let mut ws = LCUWebSocket::new_with_source(source, retry).unwrap();
+
+        let guard = ws.subscribe_guarded(EventKind::Log, ignore).unwrap();
+        let first = guard.id().0;
+        drop(guard);
+
+        let second = ws.subscribe(EventKind::Log, ignore).unwrap();
+        assert_eq!(second.0, first);
+        let third = ws.subscribe(EventKind::Log, ignore).unwrap();
+        assert_ne!(third.0, second.0);
+
+        let detached = ws
+            .subscribe_guarded(EventKind::JsonApiEvent, ignore)
+            .unwrap()
+            .detach();
+        assert!(ws.unsubscribe(EventKind::JsonApiEvent, detached).is_some());
+
+        // Unsubscribing by hand while the guard is alive frees the id only once
+        let guard = ws.subscribe_guarded(EventKind::Log, ignore).unwrap();
+        assert!(ws.unsubscribe(EventKind::Log, guard.id()).is_some());
+        assert!(ws.unsubscribe(EventKind::Log, guard.id()).is_some());
+        let reused = ws.subscribe(EventKind::Log, ignore).unwrap();
+        assert_eq!(reused.0, guard.id().0);
+
+        // So dropping the guard leaves the subscriber that was handed its id alone
+        drop(guard);
+        let next = ws.subscribe(EventKind::Log, ignore).unwrap();
+        assert_ne!(next.0, reused.0);
+
+        assert_eq!(ws.close(), Some(()));
+    }
+
+    #[cfg(feature = "mock")]
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_mock_drop_closes() {
+        use crate::mock::MockServer;
+        use std::sync::Arc;
+        use std::time::{Duration, Instant};
+
+        let server = MockServer::builder().start().await.unwrap();
+
+        let mut ws = LCUWebSocket::new_with_tls_config(
+            server.credentials(),
+            |_: WebsocketError| ControlFlow::Continue(Flow::TryReconnect),
+            Arc::new(server.client_config()),
+        )
+        .unwrap();
+
+        let _subscription = ws.subscribe_guarded(EventKind::Log, ignore).unwrap();
+        wait_for_subscription(&server, "OnLog").await;
+
+        // The guard outlives the websocket, but the connection still goes away
+        drop(ws);
+
+        let deadline = Instant::now() + Duration::from_secs(5);
+        while server.subscribed("OnLog") {
+            assert!(Instant::now() < deadline, "the connection was never closed");
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+    }
+
+    #[cfg(feature = "mock")]
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_mock_subscribe_and_call() {
+        use crate::mock::MockServer;
+        use hyper::{Method, StatusCode};
+        use std::sync::{mpsc, Arc};
+        use std::time::Duration;
+
+        let server = MockServer::builder()
+            .route(
+                Method::GET,
+                "/lol-gameflow/v1/gameflow-phase",
+                StatusCode::OK,
+                json!("Lobby"),
+            )
+            .start()
+            .await
+            .unwrap();
+
+        let mut ws = LCUWebSocket::new_with_tls_config(
+            server.credentials(),
+            |_: WebsocketError| ControlFlow::Continue(Flow::TryReconnect),
+            Arc::new(server.client_config()),
+        )
+        .unwrap();
+
+        let (sender, receiver) = mpsc::channel();
+        let _subscription = ws
+            .subscribe_guarded(
+                EventKind::JsonApiEventCallback("/lol-gameflow/v1/gameflow-phase".into()),
+                move |event: &Event| {
+                    let _ = sender.send(event.2.data.clone());
+                    ControlFlow::Continue(Flow::Continue)
+                },
+            )
+            .unwrap();
+
+        wait_for_subscription(&server, "OnJsonApiEvent_lol-gameflow_v1_gameflow-phase").await;
+
+        server.publish(
+            "/lol-gameflow/v1/gameflow-phase",
+            &EventType::Update,
+            json!("ChampSelect"),
+        );
+        let data = receiver.recv_timeout(Duration::from_secs(5)).unwrap();
+        assert_eq!(data, json!("ChampSelect"));
+
+        let reply = ws
+            .call("GET /lol-gameflow/v1/gameflow-phase", Vec::new())
+            .unwrap()
+            .recv_timeout(Duration::from_secs(5))
+            .unwrap();
+        assert_eq!(reply, Ok(json!("Lobby")));
+
+        let missing = ws
+            .call("GET /lol-missing/v1/endpoint", Vec::new())
+            .unwrap()
+            .recv_timeout(Duration::from_secs(5))
+            .unwrap();
+        assert!(missing.is_err());
+
+        assert_eq!(ws.close(), Some(()));
+    }
+
+    #[cfg(feature = "mock")]
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_mock_call_timeout_while_disconnected() {
+        use crate::mock::MockServer;
+        use crate::ws::call::CallError;
+        use std::net::TcpStream;
+        use std::sync::Arc;
+        use std::time::{Duration, Instant};
+
+        let server = MockServer::builder().start().await.unwrap();
+        let addr = server.addr();
+        let credentials = server.credentials();
+        let tls = Arc::new(server.client_config());
+
+        // The listener is closed once the accept loop has been aborted
+        drop(server);
+        while TcpStream::connect(addr).is_ok() {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+
+        let ws = LCUWebSocket::new_with_tls_config(
+            credentials,
+            |_: WebsocketError| ControlFlow::Continue(Flow::TryReconnect),
+            tls,
+        )
+        .unwrap();
+
+        let timeout = Duration::from_millis(200);
+        let started = Instant::now();
+        let reply = ws
+            .call_with_timeout("GET /lol-gameflow/v1/gameflow-phase", Vec::new(), timeout)
+            .unwrap()
+            .recv_timeout(Duration::from_secs(5))
+            .unwrap();
+
+        assert_eq!(reply, Err(CallError::Timeout));
+        assert!(started.elapsed() >= timeout);
+
+        assert_eq!(ws.close(), Some(()));
+    }
+
+    #[test]
+    fn test_invalid_auth_header() {
+        use std::net::SocketAddr;
+
+        let addr = SocketAddr::from(([127, 0, 0, 1], 2999));
+
+        assert!(super::handshake_request(addr, "Basic cmlvdDpwYXNz").is_ok());
+        // A newline would let a credential source inject other headers
+        assert!(matches!(
+            super::handshake_request(addr, "Basic cmlvdDpwYXNz\r\nX-Other: 1"),
+            Err(tungstenite::Error::HttpFormat(_))
+        ));
+    }
+
+    #[test]
+    fn test_unknown_frames() {
+        let json = json!([42, "OnSomethingNew_with_callback", {
+            "data": null,
+            "eventType": "Update",
+            "uri": "/Example/Uri"
+        }]);
+        let event: Event = serde_json::from_value(json).unwrap();
+        assert_eq!(event.0, RequestType::Unknown(42));
+        assert_eq!(
+            event.1,
+            EventKind::Other("OnSomethingNew_with_callback".into())
+        );
+
+        let json = serde_json::to_value(&event).unwrap();
+        assert_eq!(json[0], 42);
+        assert_eq!(json[1], "OnSomethingNew_with_callback");
+
+        let json = json!([8, "OnLog", {
+            "data": null,
+            "eventType": "Create",
+            "uri": ""
+        }]);
+        let event: Event = serde_json::from_value(json).unwrap();
+        assert_eq!(event.1, EventKind::Log);
+
+        assert!(serde_json::from_slice::<Event>(b"[8, 5]").is_err());
+        assert_eq!(
+            EventKind::LcdsEventCallback(String::new()).to_string(),
+            "OnLcdsEvent_"
+        );
+    }
+
+    #[cfg(feature = "mock")]
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_mock_reconnect() {
+        use crate::mock::MockServer;
+        use std::sync::{mpsc, Arc};
+        use std::time::Duration;
+
+        const URI: &str = "/lol-gameflow/v1/gameflow-phase";
+        const EVENT_NAME: &str = "OnJsonApiEvent_lol-gameflow_v1_gameflow-phase";
+        const TIMEOUT: Duration = Duration::from_secs(5);
+
+        let server = MockServer::builder().start().await.unwrap();
+
+        let (error_sender, errors) = mpsc::channel();
+        let mut ws = LCUWebSocket::new_with_tls_config(
+            server.credentials(),
+            move |e: WebsocketError| {
+                let _ = error_sender.send(e);
+                ControlFlow::Continue(Flow::TryReconnect)
+            },
+            Arc::new(server.client_config()),
+        )
+        .unwrap();
+
+        let (data_sender, data) = mpsc::channel();
+        let (connection_sender, connection) = mpsc::channel();
+
+        // Subscribers are called in order, so this one asks to reconnect before the other sees the event
+        let kind = EventKind::JsonApiEventCallback(URI.into());
+        ws.subscribe(kind.clone(), |event: &Event| {
+            if event.2.data == json!("Reconnect") {
+                ControlFlow::Continue(Flow::TryReconnect)
+            } else {
+                ControlFlow::Continue(Flow::Continue)
+            }
+        })
+        .unwrap();
+        ws.subscribe(
+            kind,
+            Recording {
+                data: data_sender,
+                connection: connection_sender,
+            },
+        )
+        .unwrap();
+
+        wait_for_subscription(&server, EVENT_NAME).await;
+
+        // The LCU forgets every subscription along with the connection
+        server.disconnect();
+        assert_eq!(
+            connection.recv_timeout(TIMEOUT),
+            Ok(ConnectionEvent::Disconnected)
+        );
+        assert_eq!(
+            connection.recv_timeout(TIMEOUT),
+            Ok(ConnectionEvent::Reconnected)
+        );
+
+        // The close frame carries a reason, which isn't mistaken for an event
+        assert!(!errors
+            .try_iter()
+            .any(|e| matches!(e, WebsocketError::SerdeJson(_))));
+
+        wait_for_subscription(&server, EVENT_NAME).await;
+        server.publish(URI, &EventType::Update, json!("Lobby"));
+        assert_eq!(data.recv_timeout(TIMEOUT), Ok(json!("Lobby")));
+
+        // A subscriber asking to reconnect doesn't keep the event from the next one
+        server.publish(URI, &EventType::Update, json!("Reconnect"));
+        assert_eq!(data.recv_timeout(TIMEOUT), Ok(json!("Reconnect")));
+        assert_eq!(
+            connection.recv_timeout(TIMEOUT),
+            Ok(ConnectionEvent::Disconnected)
+        );
+        assert_eq!(
+            connection.recv_timeout(TIMEOUT),
+            Ok(ConnectionEvent::Reconnected)
+        );
+
+        assert_eq!(ws.close(), Some(()));
+    }
 
     #[test]
     fn test_deserialize() {
@@ -428,5 +1369,19 @@ mod test {
         }]);
         let event: Event = serde_json::from_value(json).unwrap();
         println!("{event:?}");
+        assert_eq!(event.2.event_type, EventType::Create);
+
+        let json = json!([8, "OnJsonApiEvent", {
+            "data": [1, 2, 3],
+            "eventType": "Replace",
+            "uri": "/Example/Uri"
+        }]);
+        let event: Event = serde_json::from_value(json).unwrap();
+        assert_eq!(event.2.event_type, EventType::Other("Replace".into()));
+        assert_eq!(event.2.data_as::<Vec<u8>>().unwrap(), [1, 2, 3]);
+        assert!(event.2.data_as::<String>().is_err());
+
+        let json = serde_json::to_value(&event).unwrap();
+        assert_eq!(json[2]["eventType"], "Replace");
     }
 }
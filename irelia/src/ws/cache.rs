@@ -0,0 +1,347 @@
+//! A live copy of LCU endpoints, kept up to date by the websocket
+//!
+//! The cache takes its initial values from `LcuClient::get`, and then applies every
+//! `OnJsonApiEvent` for the tracked URIs, so it always holds the latest JSON for each
+//!
+//! Events sent while the websocket is disconnected are lost, so the cache is cleared
+//! when the connection drops, and needs another snapshot once it is back
+
+use std::collections::HashMap;
+use std::ops::ControlFlow;
+use std::sync::mpsc::{Receiver, Sender};
+use std::sync::{Arc, PoisonError, RwLock};
+
+use hyper::StatusCode;
+use serde::de::DeserializeOwned;
+use serde_json::Value;
+
+use crate::rest::LcuClient;
+use crate::ws::types::{Event, EventData, EventKind, EventType};
+use crate::ws::{ConnectionEvent, Flow, LCUWebSocket, Subscriber, Subscription};
+use crate::{Error, RequestClient};
+
+/// A change to one of the tracked URIs
+#[derive(Debug, Clone, PartialEq)]
+pub struct StateChange {
+    pub uri: String,
+    pub event_type: EventType,
+    /// The new value, or `None` if it was deleted
+    pub data: Option<Value>,
+}
+
+#[derive(Default)]
+struct Inner {
+    tracked: Vec<String>,
+    state: HashMap<String, Value>,
+    watchers: Vec<Sender<StateChange>>,
+}
+
+/// Map of URI to the latest JSON the LCU has sent for it
+///
+/// Cloning the cache is cheap, and every clone shares the same state
+#[derive(Clone, Default)]
+pub struct StateCache {
+    inner: Arc<RwLock<Inner>>,
+}
+
+impl StateCache {
+    #[must_use]
+    /// Creates a cache which tracks the given URIs, such as `/lol-gameflow/v1/session`
+    pub fn new<I>(uris: I) -> Self
+    where
+        I: IntoIterator,
+        I::Item: Into<String>,
+    {
+        let cache = Self::default();
+
+        for uri in uris {
+            cache.track(uri);
+        }
+
+        cache
+    }
+
+    /// Starts tracking another URI, it will be empty until the next event or snapshot
+    ///
+    /// A cache that is already attached to a websocket has to be attached again
+    /// to receive events for the new URI
+    pub fn track(&self, uri: impl Into<String>) {
+        let uri = uri.into();
+        let mut inner = self.write();
+
+        if !inner.tracked.contains(&uri) {
+            inner.tracked.push(uri);
+        }
+    }
+
+    #[must_use]
+    pub fn tracked(&self) -> Vec<String> {
+        self.read().tracked.clone()
+    }
+
+    /// Fetches the current value of every tracked URI
+    ///
+    /// Attach the cache to the websocket first, so no events are missed between the two,
+    /// this should also be called again after the websocket reconnects, as the cache
+    /// is cleared when the connection drops
+    ///
+    /// # Errors
+    /// This will return the first error from the LCU, other than a 404,
+    /// which is treated as the URI having no value
+    pub async fn snapshot(
+        &self,
+        client: &LcuClient,
+        request_client: &RequestClient,
+    ) -> Result<(), Error> {
+        for uri in self.tracked() {
+            match client.get::<Value>(&uri, request_client).await {
+                Ok(data) => self.set(&uri, EventType::Update, Some(data)),
+                Err(Error::Lcu {
+                    status: StatusCode::NOT_FOUND,
+                    ..
+                }) => self.set(&uri, EventType::Delete, None),
+                Err(e) => return Err(e),
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Subscribes the cache to the `OnJsonApiEvent` callback of each tracked URI,
+    /// events stop being applied once the returned guards are dropped
+    ///
+    /// Returns `None` is the websocket connection has already been closed previously
+    pub fn attach(&self, ws: &mut LCUWebSocket) -> Option<Vec<Subscription>> {
+        self.tracked()
+            .into_iter()
+            .map(|uri| {
+                ws.subscribe_guarded(EventKind::JsonApiEventCallback(uri), self.subscriber())
+            })
+            .collect()
+    }
+
+    #[must_use]
+    /// Returns a subscriber which applies events to the cache,
+    /// for use with `LCUWebSocket::subscribe`
+    pub fn subscriber(&self) -> CacheSubscriber {
+        CacheSubscriber(self.clone())
+    }
+
+    #[must_use]
+    /// Applies an event to the cache, returning false if the URI isn't tracked
+    ///
+    /// This can be used to feed the cache from `LCUWebSocketStream`
+    pub fn apply(&self, event: &EventData) -> bool {
+        if !self.read().tracked.contains(&event.uri) {
+            return false;
+        }
+
+        let data = match event.event_type {
+            EventType::Delete => None,
+            _ => Some(event.data.clone()),
+        };
+
+        self.set(&event.uri, event.event_type.clone(), data);
+
+        true
+    }
+
+    #[must_use]
+    /// Returns the latest value for the URI, if it has one
+    pub fn get(&self, uri: &str) -> Option<Value> {
+        self.read().state.get(uri).cloned()
+    }
+
+    /// Returns the latest value for the URI, decoded into `T`
+    ///
+    /// # Errors
+    /// This will return an error if the value does not match the type
+    pub fn get_as<T: DeserializeOwned>(&self, uri: &str) -> Result<Option<T>, serde_json::Error> {
+        self.read().state.get(uri).map(T::deserialize).transpose()
+    }
+
+    #[must_use]
+    /// Returns a receiver which is sent every change to the tracked URIs,
+    /// changes that leave the value as it was are skipped
+    pub fn changes(&self) -> Receiver<StateChange> {
+        let (sender, receiver) = std::sync::mpsc::channel();
+
+        self.write().watchers.push(sender);
+
+        receiver
+    }
+
+    /// Removes the value of every tracked URI, sending a `Delete` change for each
+    pub fn clear(&self) {
+        for uri in self.tracked() {
+            self.set(&uri, EventType::Delete, None);
+        }
+    }
+
+    fn set(&self, uri: &str, event_type: EventType, data: Option<Value>) {
+        let mut inner = self.write();
+
+        let previous = match &data {
+            Some(data) => inner.state.insert(uri.to_string(), data.clone()),
+            None => inner.state.remove(uri),
+        };
+
+        if previous == data {
+            return;
+        }
+
+        let change = StateChange {
+            uri: uri.to_string(),
+            event_type,
+            data,
+        };
+
+        // Receivers that have been dropped are removed
+        inner
+            .watchers
+            .retain(|watcher| watcher.send(change.clone()).is_ok());
+    }
+
+    // A poisoned lock only means a watcher panicked mid-update, the map itself is still valid
+    fn read(&self) -> std::sync::RwLockReadGuard<'_, Inner> {
+        self.inner.read().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    fn write(&self) -> std::sync::RwLockWriteGuard<'_, Inner> {
+        self.inner.write().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+/// Subscriber which applies every event to a `StateCache`,
+/// and clears it when the connection drops
+pub struct CacheSubscriber(StateCache);
+
+impl Subscriber for CacheSubscriber {
+    fn on_event(&mut self, event: &Event) -> ControlFlow<(), Flow> {
+        // Events for other URIs are expected when subscribed to every `OnJsonApiEvent`
+        let _ = self.0.apply(&event.2);
+
+        ControlFlow::Continue(Flow::Continue)
+    }
+
+    fn on_connection_event(&mut self, event: ConnectionEvent) {
+        // Whatever changed while disconnected was never sent, so the values can't be trusted
+        if event == ConnectionEvent::Disconnected {
+            self.0.clear();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{StateCache, StateChange};
+    use crate::ws::types::{EventData, EventType};
+    use serde_json::{json, Value};
+
+    fn event(uri: &str, event_type: EventType, data: Value) -> EventData {
+        EventData {
+            data,
+            event_type,
+            uri: uri.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_state_cache() {
+        let cache = StateCache::new(["/lol-gameflow/v1/gameflow-phase"]);
+        let changes = cache.changes();
+
+        let phase = "/lol-gameflow/v1/gameflow-phase";
+        assert!(cache.apply(&event(phase, EventType::Create, json!("Lobby"))));
+        assert!(!cache.apply(&event("/lol-lobby/v2/lobby", EventType::Create, json!({}))));
+        assert_eq!(cache.get_as::<String>(phase).unwrap().unwrap(), "Lobby");
+        assert!(cache.get_as::<u32>(phase).is_err());
+
+        // Updates that don't change anything aren't sent
+        assert!(cache.apply(&event(phase, EventType::Update, json!("Lobby"))));
+        assert!(cache.apply(&event(phase, EventType::Delete, Value::Null)));
+        assert_eq!(cache.get(phase), None);
+
+        let changes: Vec<StateChange> = changes.try_iter().collect();
+        assert_eq!(
+            changes,
+            [
+                StateChange {
+                    uri: phase.into(),
+                    event_type: EventType::Create,
+                    data: Some(json!("Lobby")),
+                },
+                StateChange {
+                    uri: phase.into(),
+                    event_type: EventType::Delete,
+                    data: None,
+                },
+            ]
+        );
+    }
+
+    #[cfg(feature = "mock")]
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_mock_state_cache() {
+        use crate::mock::MockServer;
+        use crate::ws::{Flow, LCUWebSocket};
+        use hyper::{Method, StatusCode};
+        use std::ops::ControlFlow;
+        use std::sync::Arc;
+        use std::time::{Duration, Instant};
+
+        const URI: &str = "/lol-gameflow/v1/gameflow-phase";
+        const EVENT_NAME: &str = "OnJsonApiEvent_lol-gameflow_v1_gameflow-phase";
+
+        async fn wait_until(condition: impl Fn() -> bool) {
+            let deadline = Instant::now() + Duration::from_secs(5);
+
+            while !condition() {
+                assert!(Instant::now() < deadline, "the condition was never met");
+                tokio::time::sleep(Duration::from_millis(10)).await;
+            }
+        }
+
+        let server = MockServer::builder()
+            .route(Method::GET, URI, StatusCode::OK, json!("Lobby"))
+            .start()
+            .await
+            .unwrap();
+
+        let mut ws = LCUWebSocket::new_with_tls_config(
+            server.credentials(),
+            |_| ControlFlow::Continue(Flow::TryReconnect),
+            Arc::new(server.client_config()),
+        )
+        .unwrap();
+
+        let cache = StateCache::new([URI]);
+        let _subscriptions = cache.attach(&mut ws).unwrap();
+
+        // Only the tracked URIs are subscribed to, rather than every event
+        wait_until(|| server.subscribed(EVENT_NAME)).await;
+        assert!(!server.subscribed("OnJsonApiEvent"));
+
+        cache
+            .snapshot(&server.lcu_client(), &server.request_client())
+            .await
+            .unwrap();
+        assert_eq!(cache.get(URI), Some(json!("Lobby")));
+
+        server.publish(URI, &EventType::Update, json!("ChampSelect"));
+        wait_until(|| cache.get(URI) == Some(json!("ChampSelect"))).await;
+
+        // Anything could have changed while disconnected, so nothing stale is kept
+        server.disconnect();
+        wait_until(|| cache.get(URI).is_none()).await;
+
+        wait_until(|| server.subscribed(EVENT_NAME)).await;
+        cache
+            .snapshot(&server.lcu_client(), &server.request_client())
+            .await
+            .unwrap();
+        assert_eq!(cache.get(URI), Some(json!("Lobby")));
+
+        assert_eq!(ws.close(), Some(()));
+    }
+}
@@ -0,0 +1,265 @@
+//! WAMP 1.0 remote procedure calls over the LCU websocket
+//!
+//! Calls are sent as `[2, callID, procURI, args...]`, and the LCU replies with either
+//! `[3, callID, result]` or `[4, callID, errorURI, errorDesc, errorDetails?]`
+
+use std::collections::HashMap;
+use std::fmt::{Display, Formatter};
+use std::sync::mpsc::Sender;
+use std::time::Instant;
+
+use serde_json::Value;
+
+use crate::ws::types::RequestType;
+
+/// The result of a call, as sent back by the event loop
+pub type CallResult = Result<Value, CallError>;
+
+/// Errors that can be returned from `LCUWebSocket::call`
+#[derive(Debug, Clone, PartialEq)]
+pub enum CallError {
+    /// The LCU replied with a `CallError` frame
+    Remote {
+        error_uri: String,
+        description: Value,
+        details: Option<Value>,
+    },
+    /// No reply was received before the timeout passed
+    Timeout,
+    /// The connection was lost before a reply was received, the call may or may not
+    /// have been handled by the LCU
+    Disconnected,
+    /// The reply did not follow the WAMP format
+    Malformed(Value),
+}
+
+impl Display for CallError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CallError::Remote {
+                error_uri,
+                description,
+                ..
+            } => write!(f, "{error_uri}: {description}"),
+            CallError::Timeout => f.write_str("The call timed out"),
+            CallError::Disconnected => f.write_str("The connection was lost during the call"),
+            CallError::Malformed(frame) => write!(f, "Malformed reply: {frame}"),
+        }
+    }
+}
+
+impl std::error::Error for CallError {}
+
+struct PendingCall {
+    deadline: Instant,
+    sender: Sender<CallResult>,
+}
+
+/// A call made while disconnected, which is sent once the connection is back
+struct QueuedCall {
+    uri: String,
+    args: Vec<Value>,
+    call: PendingCall,
+}
+
+/// Calls that have been sent, but not yet replied to, along with the ones waiting to be sent
+#[derive(Default)]
+pub(super) struct PendingCalls {
+    next_id: u64,
+    calls: HashMap<String, PendingCall>,
+    queued: Vec<QueuedCall>,
+}
+
+impl PendingCalls {
+    /// Stores the sender, returning the id the call should be sent with
+    pub(super) fn insert(&mut self, deadline: Instant, sender: Sender<CallResult>) -> String {
+        let id = self.next_id.to_string();
+        self.next_id += 1;
+
+        self.calls
+            .insert(id.clone(), PendingCall { deadline, sender });
+
+        id
+    }
+
+    /// Keeps a call until there is a connection to send it on, see `send_queued`
+    pub(super) fn queue(
+        &mut self,
+        uri: String,
+        args: Vec<Value>,
+        deadline: Instant,
+        sender: Sender<CallResult>,
+    ) {
+        self.queued.push(QueuedCall {
+            uri,
+            args,
+            call: PendingCall { deadline, sender },
+        });
+    }
+
+    /// Moves every queued call to the sent ones, returning the frames to send, in the order
+    /// the calls were made
+    pub(super) fn send_queued(&mut self) -> Vec<String> {
+        let queued = std::mem::take(&mut self.queued);
+
+        queued
+            .into_iter()
+            .map(|queued| {
+                let id = self.insert(queued.call.deadline, queued.call.sender);
+                call_frame(id, queued.uri, queued.args)
+            })
+            .collect()
+    }
+
+    /// Completes the matching call if the frame is a `CallResult` or `CallError`,
+    /// returning false for every other kind of frame
+    pub(super) fn resolve(&mut self, frame: &Value) -> bool {
+        let Some(parts) = frame.as_array() else {
+            return false;
+        };
+
+        let code = parts.first().and_then(Value::as_u64);
+
+        if code != Some(RequestType::CallResult.code())
+            && code != Some(RequestType::CallError.code())
+        {
+            return false;
+        }
+
+        let Some(call) = parts
+            .get(1)
+            .and_then(Value::as_str)
+            .and_then(|id| self.calls.remove(id))
+        else {
+            // Either a reply to a call that has already timed out, or to one we never made
+            return true;
+        };
+
+        let result = if code == Some(RequestType::CallResult.code()) {
+            Ok(parts.get(2).cloned().unwrap_or(Value::Null))
+        } else if let Some(error_uri) = parts.get(2).and_then(Value::as_str) {
+            Err(CallError::Remote {
+                error_uri: error_uri.to_string(),
+                description: parts.get(3).cloned().unwrap_or(Value::Null),
+                details: parts.get(4).cloned(),
+            })
+        } else {
+            Err(CallError::Malformed(frame.clone()))
+        };
+
+        // The caller may have dropped the receiver, which is fine
+        let _ = call.sender.send(result);
+
+        true
+    }
+
+    /// Fails every call whose deadline has passed, whether it was sent or not
+    pub(super) fn expire(&mut self, now: Instant) {
+        let expired = |call: &PendingCall| {
+            if call.deadline > now {
+                return false;
+            }
+
+            let _ = call.sender.send(Err(CallError::Timeout));
+            true
+        };
+
+        self.calls.retain(|_, call| !expired(call));
+        self.queued.retain(|queued| !expired(&queued.call));
+    }
+
+    /// Fails every call with the same error, including the queued ones
+    pub(super) fn fail_all(&mut self, error: &CallError) {
+        let calls = self
+            .calls
+            .drain()
+            .map(|(_, call)| call)
+            .chain(self.queued.drain(..).map(|queued| queued.call));
+
+        for call in calls {
+            let _ = call.sender.send(Err(error.clone()));
+        }
+    }
+}
+
+/// Formats a call frame
+pub(super) fn call_frame(id: String, uri: String, args: Vec<Value>) -> String {
+    let mut frame = Vec::with_capacity(args.len() + 3);
+
+    frame.push(Value::from(RequestType::Call.code()));
+    frame.push(Value::String(id));
+    frame.push(Value::String(uri));
+    frame.extend(args);
+
+    Value::Array(frame).to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{call_frame, CallError, PendingCalls};
+    use serde_json::json;
+    use std::sync::mpsc::channel;
+    use std::time::{Duration, Instant};
+
+    #[test]
+    fn test_pending_calls() {
+        let mut calls = PendingCalls::default();
+
+        let (sender, result) = channel();
+        let id = calls.insert(Instant::now() + Duration::from_secs(10), sender);
+        assert_eq!(
+            call_frame(
+                id.clone(),
+                "GetLolSummonerV1CurrentSummoner".into(),
+                vec![json!(1)]
+            ),
+            format!(r#"[2,"{id}","GetLolSummonerV1CurrentSummoner",1]"#)
+        );
+
+        assert!(!calls.resolve(&json!([8, "OnJsonApiEvent", {}])));
+        assert!(calls.resolve(&json!([3, id, {"summonerLevel": 30}])));
+        assert_eq!(result.recv().unwrap(), Ok(json!({"summonerLevel": 30})));
+
+        let (sender, error) = channel();
+        let id = calls.insert(Instant::now() + Duration::from_secs(10), sender);
+        assert!(calls.resolve(&json!([4, id, "RPC_ERROR", "Not Found"])));
+        assert_eq!(
+            error.recv().unwrap(),
+            Err(CallError::Remote {
+                error_uri: "RPC_ERROR".into(),
+                description: json!("Not Found"),
+                details: None,
+            })
+        );
+
+        let (sender, timeout) = channel();
+        calls.insert(Instant::now(), sender);
+        let (sender, disconnected) = channel();
+        calls.insert(Instant::now() + Duration::from_secs(10), sender);
+
+        calls.expire(Instant::now());
+        assert_eq!(timeout.recv().unwrap(), Err(CallError::Timeout));
+
+        calls.fail_all(&CallError::Disconnected);
+        assert_eq!(disconnected.recv().unwrap(), Err(CallError::Disconnected));
+
+        // Calls made while disconnected can time out before they are sent
+        let deadline = Instant::now() + Duration::from_secs(10);
+        let (sender, queued) = channel();
+        calls.queue("GET /first".into(), Vec::new(), deadline, sender);
+        let (sender, timeout) = channel();
+        calls.queue("GET /late".into(), Vec::new(), Instant::now(), sender);
+
+        calls.expire(Instant::now());
+        assert_eq!(timeout.recv().unwrap(), Err(CallError::Timeout));
+
+        let frames = calls.send_queued();
+        assert_eq!(frames.len(), 1);
+        let frame: serde_json::Value = serde_json::from_str(&frames[0]).unwrap();
+        assert_eq!(frame[2], "GET /first");
+        assert!(calls.send_queued().is_empty());
+
+        assert!(calls.resolve(&json!([3, frame[1], "done"])));
+        assert_eq!(queued.recv().unwrap(), Ok(json!("done")));
+    }
+}
@@ -0,0 +1,184 @@
+//! Client side filtering of `OnJsonApiEvent`
+//!
+//! Rather than subscribing to the mangled path of every endpoint, a single
+//! subscription to `EventKind::JsonApiEvent` can be split up by URI pattern.
+//!
+//! Patterns are split on `/`, and each segment is one of:
+//! - a literal, which must match exactly
+//! - `*`, which matches any single segment
+//! - `**`, which matches any number of segments, including none
+//! - `{name}`, which matches any single segment, and captures it as a parameter
+
+use std::collections::HashMap;
+use std::ops::ControlFlow;
+
+use crate::ws::types::{Event, EventData, EventType};
+use crate::ws::{ConnectionEvent, Flow, Subscriber};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Segment {
+    Literal(String),
+    Param(String),
+    Any,
+    Rest,
+}
+
+/// A URI pattern, optionally restricted to specific event types
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UriFilter {
+    segments: Vec<Segment>,
+    event_types: Option<Vec<EventType>>,
+}
+
+impl UriFilter {
+    #[must_use]
+    /// Creates a filter that matches the pattern, for any event type
+    pub fn new(pattern: &str) -> Self {
+        let segments = pattern
+            .split('/')
+            .map(|segment| match segment {
+                "*" => Segment::Any,
+                "**" => Segment::Rest,
+                param if param.len() > 1 && param.starts_with('{') && param.ends_with('}') => {
+                    Segment::Param(param[1..param.len() - 1].to_string())
+                }
+                literal => Segment::Literal(literal.to_string()),
+            })
+            .collect();
+
+        Self {
+            segments,
+            event_types: None,
+        }
+    }
+
+    #[must_use]
+    /// Only match events with one of the given types
+    pub fn with_event_types(mut self, event_types: impl IntoIterator<Item = EventType>) -> Self {
+        self.event_types = Some(event_types.into_iter().collect());
+        self
+    }
+
+    #[must_use]
+    /// Returns true if both the URI and the event type of the event match
+    pub fn matches(&self, data: &EventData) -> bool {
+        let type_matches = self
+            .event_types
+            .as_ref()
+            .is_none_or(|types| types.contains(&data.event_type));
+
+        type_matches && self.params(&data.uri).is_some()
+    }
+
+    #[must_use]
+    /// Matches the URI against the pattern, returning the captured parameters if it matched
+    pub fn params<'a>(&'a self, uri: &'a str) -> Option<HashMap<&'a str, &'a str>> {
+        let uri: Vec<&str> = uri.split('/').collect();
+        let mut params = HashMap::new();
+
+        match_segments(&self.segments, &uri, &mut params).then_some(params)
+    }
+}
+
+fn match_segments<'a>(
+    pattern: &'a [Segment],
+    uri: &[&'a str],
+    params: &mut HashMap<&'a str, &'a str>,
+) -> bool {
+    let Some((segment, pattern_rest)) = pattern.split_first() else {
+        return uri.is_empty();
+    };
+
+    if *segment == Segment::Rest {
+        return (0..=uri.len()).any(|skip| match_segments(pattern_rest, &uri[skip..], params));
+    }
+
+    let Some((part, uri_rest)) = uri.split_first() else {
+        return false;
+    };
+
+    match segment {
+        Segment::Literal(literal) if literal != part => return false,
+        Segment::Param(name) => {
+            params.insert(name, part);
+        }
+        _ => {}
+    }
+
+    match_segments(pattern_rest, uri_rest, params)
+}
+
+/// Wraps a subscriber, only forwarding events that match the filter
+pub struct Filtered<S> {
+    filter: UriFilter,
+    subscriber: S,
+}
+
+impl<S: Subscriber> Filtered<S> {
+    #[must_use]
+    pub fn new(filter: UriFilter, subscriber: S) -> Self {
+        Self { filter, subscriber }
+    }
+
+    #[must_use]
+    pub fn filter(&self) -> &UriFilter {
+        &self.filter
+    }
+}
+
+impl<S: Subscriber> Subscriber for Filtered<S> {
+    fn on_event(&mut self, event: &Event) -> ControlFlow<(), Flow> {
+        if self.filter.matches(&event.2) {
+            self.subscriber.on_event(event)
+        } else {
+            ControlFlow::Continue(Flow::Continue)
+        }
+    }
+
+    fn on_connection_event(&mut self, event: ConnectionEvent) {
+        self.subscriber.on_connection_event(event);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::UriFilter;
+    use crate::ws::types::{EventData, EventType};
+    use serde_json::Value;
+
+    fn event(uri: &str, event_type: EventType) -> EventData {
+        EventData {
+            data: Value::Null,
+            event_type,
+            uri: uri.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_uri_filter() {
+        let gameflow = UriFilter::new("/lol-gameflow/v1/*");
+        assert!(gameflow.matches(&event("/lol-gameflow/v1/session", EventType::Update)));
+        assert!(!gameflow.matches(&event("/lol-gameflow/v1", EventType::Update)));
+        assert!(!gameflow.matches(&event("/lol-gameflow/v1/session/extra", EventType::Update)));
+        assert!(!gameflow.matches(&event("/lol-lobby/v1/session", EventType::Update)));
+
+        let everything = UriFilter::new("/lol-gameflow/**");
+        assert!(everything.matches(&event("/lol-gameflow", EventType::Update)));
+        assert!(everything.matches(&event("/lol-gameflow/v1/session/extra", EventType::Update)));
+
+        let members = UriFilter::new("/lol-lobby/v2/lobby/members/{id}")
+            .with_event_types([EventType::Create, EventType::Delete]);
+        assert!(members.matches(&event(
+            "/lol-lobby/v2/lobby/members/1234",
+            EventType::Create
+        )));
+        assert!(!members.matches(&event(
+            "/lol-lobby/v2/lobby/members/1234",
+            EventType::Update
+        )));
+
+        let params = members.params("/lol-lobby/v2/lobby/members/1234").unwrap();
+        assert_eq!(params["id"], "1234");
+        assert!(members.params("/lol-lobby/v2/lobby/members").is_none());
+    }
+}
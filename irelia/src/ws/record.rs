@@ -0,0 +1,246 @@
+//! Recording websocket sessions to JSON Lines, and playing them back
+//!
+//! Each line holds one event, alongside the time it was received in milliseconds
+//! since the unix epoch, such as `{"timestamp":1718000000000,"event":[8,"OnJsonApiEvent",{...}]}`.
+//!
+//! Played back events go through the same dispatch as a live connection,
+//! so subscribers can be tested without the client running
+
+use std::fs::File;
+use std::io::{BufRead, BufReader, BufWriter, Write};
+use std::ops::ControlFlow;
+use std::path::Path;
+use std::thread;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use serde_derive::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::ws::types::{Event, EventKind};
+use crate::ws::{dispatch, ErrorHandler, Flow, Listeners, Subscriber};
+
+#[derive(Serialize)]
+struct RecordLine<'a> {
+    timestamp: u64,
+    event: &'a Event,
+}
+
+#[derive(Deserialize)]
+struct PlaybackLine {
+    timestamp: u64,
+    event: Value,
+}
+
+/// Subscriber which writes every event it receives to a JSON Lines file
+pub struct Recorder<W: Write> {
+    writer: Option<W>,
+}
+
+impl Recorder<BufWriter<File>> {
+    /// Creates or truncates the file at the path, and records to it
+    ///
+    /// # Errors
+    /// This will return an error if the file cannot be created
+    pub fn create(path: impl AsRef<Path>) -> std::io::Result<Self> {
+        Ok(Self::new(BufWriter::new(File::create(path)?)))
+    }
+}
+
+impl<W: Write> Recorder<W> {
+    #[must_use]
+    pub fn new(writer: W) -> Self {
+        Self {
+            writer: Some(writer),
+        }
+    }
+
+    #[must_use]
+    /// Returns the writer, or `None` if writing to it failed and recording stopped
+    pub fn into_inner(self) -> Option<W> {
+        self.writer
+    }
+
+    fn record(writer: &mut W, event: &Event) -> std::io::Result<()> {
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |since| {
+                u64::try_from(since.as_millis()).unwrap_or(u64::MAX)
+            });
+
+        serde_json::to_writer(&mut *writer, &RecordLine { timestamp, event })?;
+        writer.write_all(b"\n")?;
+
+        // Flushing every line means nothing is lost if the process is killed
+        writer.flush()
+    }
+}
+
+impl<W: Write + Send> Subscriber for Recorder<W> {
+    fn on_event(&mut self, event: &Event) -> ControlFlow<(), Flow> {
+        if let Some(writer) = &mut self.writer {
+            // A broken recording shouldn't take the rest of the subscribers down with it
+            if let Err(error) = Self::record(writer, event) {
+                #[cfg(feature = "tracing")]
+                tracing::warn!(%error, "failed to record event, recording stopped");
+                #[cfg(not(feature = "tracing"))]
+                let _ = error;
+
+                self.writer = None;
+            }
+        }
+
+        ControlFlow::Continue(Flow::Continue)
+    }
+}
+
+/// Plays back a recording made by `Recorder`, passing each event to the subscribers
+pub struct Player<R: BufRead> {
+    reader: R,
+    speed: f64,
+    listeners: Listeners,
+}
+
+impl Player<BufReader<File>> {
+    /// Opens the recording at the path
+    ///
+    /// # Errors
+    /// This will return an error if the file cannot be opened
+    pub fn open(path: impl AsRef<Path>) -> std::io::Result<Self> {
+        Ok(Self::new(BufReader::new(File::open(path)?)))
+    }
+}
+
+impl<R: BufRead> Player<R> {
+    #[must_use]
+    /// Plays back the recording in real time
+    pub fn new(reader: R) -> Self {
+        Self {
+            reader,
+            speed: 1.0,
+            listeners: Listeners::default(),
+        }
+    }
+
+    #[must_use]
+    /// Sets how much faster than real time the recording is played,
+    /// any speed that isn't above zero plays every event without waiting
+    pub fn with_speed(mut self, speed: f64) -> Self {
+        self.speed = speed;
+        self
+    }
+
+    /// Subscribes to a specific event kind, the same as `LCUWebSocket::subscribe`
+    pub fn subscribe(&mut self, event_kind: &EventKind, subscriber: impl Subscriber + 'static) {
+        self.listeners
+            .subscribers
+            .get_mut(event_kind)
+            .push(Some(Box::new(subscriber)));
+    }
+
+    /// Plays the whole recording, returning how many events were read
+    ///
+    /// Lines which cannot be parsed are passed to the error handler,
+    /// playback stops early if it, or any subscriber, returns `ControlFlow::Break`
+    ///
+    /// # Errors
+    /// This will return an error if reading from the recording fails
+    pub fn play(&mut self, mut error_handler: impl ErrorHandler) -> std::io::Result<usize> {
+        let mut previous = None;
+        let mut played = 0;
+        let mut line = String::new();
+
+        loop {
+            line.clear();
+            if self.reader.read_line(&mut line)? == 0 {
+                return Ok(played);
+            }
+
+            if line.trim().is_empty() {
+                continue;
+            }
+
+            let control = match serde_json::from_str::<PlaybackLine>(&line) {
+                Ok(recorded) => {
+                    self.wait(previous, recorded.timestamp);
+                    previous = Some(recorded.timestamp);
+                    played += 1;
+
+                    let data = recorded.event.to_string();
+                    dispatch(data.as_bytes(), &mut self.listeners, &mut error_handler)
+                }
+                Err(e) => error_handler.on_error(e.into()),
+            };
+
+            if control == ControlFlow::Break(()) {
+                return Ok(played);
+            }
+        }
+    }
+
+    fn wait(&self, previous: Option<u64>, timestamp: u64) {
+        let Some(previous) = previous else {
+            return;
+        };
+
+        if self.speed > 0.0 {
+            let gap = Duration::from_millis(timestamp.saturating_sub(previous));
+            thread::sleep(gap.div_f64(self.speed));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Player, Recorder};
+    use crate::ws::types::{Event, EventKind};
+    use crate::ws::{Flow, Subscriber, WebsocketError};
+    use serde_json::json;
+    use std::io::Cursor;
+    use std::ops::ControlFlow;
+    use std::sync::mpsc::channel;
+
+    #[test]
+    fn test_record_and_play() {
+        let mut recorder = Recorder::new(Vec::new());
+
+        for (kind, uri) in [
+            ("OnJsonApiEvent", "/lol-gameflow/v1/gameflow-phase"),
+            ("OnLog", ""),
+            ("OnJsonApiEvent", "/lol-lobby/v2/lobby"),
+        ] {
+            let event: Event = serde_json::from_value(json!([8, kind, {
+                "data": null,
+                "eventType": "Update",
+                "uri": uri
+            }]))
+            .unwrap();
+            assert!(recorder.on_event(&event) == ControlFlow::Continue(Flow::Continue));
+        }
+
+        let mut recording = recorder.into_inner().unwrap();
+        recording.extend_from_slice(b"not json\n");
+
+        let (sender, uris) = channel();
+        let (error_sender, errors) = channel();
+
+        let mut player = Player::new(Cursor::new(recording)).with_speed(0.0);
+        player.subscribe(&EventKind::JsonApiEvent, move |event: &Event| {
+            sender.send(event.2.uri.clone()).unwrap();
+            ControlFlow::Continue(Flow::Continue)
+        });
+
+        let count = player
+            .play(move |error: WebsocketError| {
+                error_sender.send(error.to_string()).unwrap();
+                ControlFlow::Continue(Flow::Continue)
+            })
+            .unwrap();
+
+        assert_eq!(count, 3);
+        assert_eq!(
+            uris.try_iter().collect::<Vec<_>>(),
+            ["/lol-gameflow/v1/gameflow-phase", "/lol-lobby/v2/lobby"]
+        );
+        assert_eq!(errors.try_iter().count(), 1);
+    }
+}
@@ -0,0 +1,193 @@
+//! An async connection to the LCU websocket, for use inside a tokio runtime
+//!
+//! Unlike `LCUWebSocket`, this does not spawn a thread, events are instead
+//! polled from the connection as a `Stream`
+
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{Context, Poll};
+
+use futures_util::{SinkExt, Stream};
+use rustls::ClientConfig;
+use tokio::net::TcpStream;
+use tokio_tungstenite::{Connector, MaybeTlsStream, WebSocketStream};
+use tungstenite::Message;
+
+use crate::utils::credentials::{CredentialSource, RunningClient};
+use crate::utils::setup_tls::connector;
+use crate::ws::handshake_request;
+use crate::ws::types::{Event, EventKind, RequestType};
+use crate::Error;
+
+/// Async connection to the LCU websocket
+///
+/// Events for every subscribed event kind are yielded in the order they arrive,
+/// the stream ends once the LCU closes the connection
+pub struct LCUWebSocketStream {
+    stream: WebSocketStream<MaybeTlsStream<TcpStream>>,
+}
+
+impl LCUWebSocketStream {
+    /// Connects to the LCU websocket of the running client
+    ///
+    /// # Errors
+    /// This function will return an error if the LCU is not running,
+    /// or if it cannot connect to the websocket
+    pub async fn connect() -> Result<Self, Error> {
+        Self::connect_with_source(RunningClient::default()).await
+    }
+
+    /// Connects to the LCU websocket, using the credentials from the given source
+    ///
+    /// The source is read on tokio's blocking thread pool, as finding the running
+    /// client scans every process
+    ///
+    /// # Errors
+    /// This function will return an error if the credentials cannot be found,
+    /// or are not a valid header value, or if it cannot connect to the websocket
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip_all))]
+    pub async fn connect_with_source(
+        source: impl CredentialSource + 'static,
+    ) -> Result<Self, Error> {
+        Self::connect_with_tls_config(source, Arc::new(connector().clone())).await
+    }
+
+    /// Same as `connect_with_source`, but with a custom TLS config,
+    /// rather than only trusting the Riot certificate
+    ///
+    /// # Errors
+    /// This function will return an error if the credentials cannot be found,
+    /// or are not a valid header value, or if it cannot connect to the websocket
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip_all))]
+    pub async fn connect_with_tls_config(
+        source: impl CredentialSource + 'static,
+        tls: Arc<ClientConfig>,
+    ) -> Result<Self, Error> {
+        let (addr, auth) = match tokio::task::spawn_blocking(move || source.credentials()).await {
+            Ok(credentials) => credentials?,
+            // The task is never aborted, so it only fails if the source panicked
+            Err(e) => std::panic::resume_unwind(e.into_panic()),
+        };
+
+        #[cfg(feature = "tracing")]
+        tracing::debug!(%addr, "connecting");
+
+        let request = handshake_request(addr, &auth)?;
+        let tls = Connector::Rustls(tls);
+
+        let (stream, _) =
+            tokio_tungstenite::connect_async_tls_with_config(request, None, false, Some(tls))
+                .await?;
+
+        Ok(Self { stream })
+    }
+
+    /// Subscribes to a specific event kind, events will be yielded by the stream
+    ///
+    /// # Errors
+    /// This will return an error if the command could not be sent
+    pub async fn subscribe(&mut self, event_kind: &EventKind) -> Result<(), Error> {
+        self.send_command(RequestType::Subscribe, event_kind).await
+    }
+
+    /// Unsubscribes from a specific event kind
+    ///
+    /// # Errors
+    /// This will return an error if the command could not be sent
+    pub async fn unsubscribe(&mut self, event_kind: &EventKind) -> Result<(), Error> {
+        self.send_command(RequestType::Unsubscribe, event_kind)
+            .await
+    }
+
+    /// Closes the connection, sending a close frame to the LCU
+    ///
+    /// # Errors
+    /// This will return an error if the close frame could not be sent
+    pub async fn close(mut self) -> Result<(), Error> {
+        Ok(self.stream.close(None).await?)
+    }
+
+    async fn send_command(
+        &mut self,
+        code: RequestType,
+        event_kind: &EventKind,
+    ) -> Result<(), Error> {
+        let command = format!("[{}, \"{}\"]", code.code(), event_kind.to_string());
+
+        #[cfg(feature = "tracing")]
+        tracing::debug!(%command, "sending command");
+
+        Ok(self.stream.send(Message::Text(command)).await?)
+    }
+}
+
+impl Stream for LCUWebSocketStream {
+    type Item = Result<Event, Error>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        loop {
+            let message = match Pin::new(&mut self.stream).poll_next(cx) {
+                Poll::Ready(Some(Ok(message))) => message,
+                Poll::Ready(Some(Err(e))) => return Poll::Ready(Some(Err(e.into()))),
+                Poll::Ready(None) => return Poll::Ready(None),
+                Poll::Pending => return Poll::Pending,
+            };
+
+            match message {
+                Message::Close(_) => return Poll::Ready(None),
+                // The LCU acknowledges commands with an empty frame,
+                // and pings are answered by tungstenite itself
+                Message::Text(_) | Message::Binary(_) => {
+                    let data = message.into_data();
+                    if !data.is_empty() {
+                        return Poll::Ready(Some(
+                            serde_json::from_slice::<Event>(&data).map_err(Error::from),
+                        ));
+                    }
+                }
+                Message::Ping(_) | Message::Pong(_) | Message::Frame(_) => {}
+            }
+        }
+    }
+}
+
+#[cfg(all(test, feature = "mock"))]
+mod tests {
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_mock_stream() {
+        use super::LCUWebSocketStream;
+        use crate::mock::MockServer;
+        use crate::ws::types::{EventKind, EventType};
+        use futures_util::StreamExt;
+        use serde_json::json;
+        use std::sync::Arc;
+        use std::time::Duration;
+
+        let server = MockServer::builder().start().await.unwrap();
+
+        let mut stream = LCUWebSocketStream::connect_with_tls_config(
+            server.credentials(),
+            Arc::new(server.client_config()),
+        )
+        .await
+        .unwrap();
+
+        stream.subscribe(&EventKind::JsonApiEvent).await.unwrap();
+        while !server.subscribed("OnJsonApiEvent") {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+
+        server.publish("/lol-lobby/v2/lobby", &EventType::Delete, json!(null));
+
+        let event = tokio::time::timeout(Duration::from_secs(5), stream.next())
+            .await
+            .unwrap()
+            .unwrap()
+            .unwrap();
+        assert_eq!(event.1, EventKind::JsonApiEvent);
+        assert_eq!(event.2.uri, "/lol-lobby/v2/lobby");
+        assert_eq!(event.2.event_type, EventType::Delete);
+
+        stream.close().await.unwrap();
+    }
+}
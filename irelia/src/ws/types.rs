@@ -1,3 +1,4 @@
+use serde::de::DeserializeOwned;
 use serde::{Deserialize, Deserializer, Serialize, Serializer};
 use serde_derive::{Deserialize, Serialize};
 use serde_json::Value;
@@ -9,15 +10,36 @@ pub struct Event(pub RequestType, pub EventKind, pub EventData);
 /// Different LCU websocket request types
 #[derive(Debug, Eq, PartialEq, Clone, Hash)]
 pub enum RequestType {
-    Welcome = 0,
-    Prefix = 1,
-    Call = 2,
-    CallResult = 3,
-    CallError = 4,
-    Subscribe = 5,
-    Unsubscribe = 6,
-    Publish = 7,
-    Event = 8,
+    Welcome,
+    Prefix,
+    Call,
+    CallResult,
+    CallError,
+    Subscribe,
+    Unsubscribe,
+    Publish,
+    Event,
+    /// Any opcode outside of 0-8
+    Unknown(u64),
+}
+
+impl RequestType {
+    #[must_use]
+    /// The opcode used for this request type
+    pub fn code(&self) -> u64 {
+        match self {
+            RequestType::Welcome => 0,
+            RequestType::Prefix => 1,
+            RequestType::Call => 2,
+            RequestType::CallResult => 3,
+            RequestType::CallError => 4,
+            RequestType::Subscribe => 5,
+            RequestType::Unsubscribe => 6,
+            RequestType::Publish => 7,
+            RequestType::Event => 8,
+            RequestType::Unknown(code) => *code,
+        }
+    }
 }
 #[derive(Debug, Eq, PartialEq, Clone, Hash)]
 /// Different event types that can be passed to the
@@ -32,16 +54,50 @@ pub enum EventKind {
     ServiceProxyUuidEvent,
     JsonApiEventCallback(String),
     LcdsEventCallback(String),
+    /// Any event the LCU sends that isn't known yet, holding the full event name
+    Other(String),
+}
+
+/// The types of change a `JsonApiEvent` can describe
+#[derive(Debug, Eq, PartialEq, Clone, Hash)]
+pub enum EventType {
+    Create,
+    Update,
+    Delete,
+    /// Any event type the LCU sends that isn't known yet
+    Other(String),
+}
+
+impl EventType {
+    #[must_use]
+    pub fn as_str(&self) -> &str {
+        match self {
+            EventType::Create => "Create",
+            EventType::Update => "Update",
+            EventType::Delete => "Delete",
+            EventType::Other(other) => other,
+        }
+    }
 }
 
 #[derive(Debug, Deserialize, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct EventData {
     pub data: Value,
-    pub event_type: String,
+    pub event_type: EventType,
     pub uri: String,
 }
 
+impl EventData {
+    /// Decodes the payload into the given type
+    ///
+    /// # Errors
+    /// This will return an error if the payload does not match the type
+    pub fn data_as<T: DeserializeOwned>(&self) -> Result<T, serde_json::Error> {
+        T::deserialize(&self.data)
+    }
+}
+
 impl<'de> Deserialize<'de> for RequestType {
     fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
     where
@@ -59,7 +115,7 @@ impl<'de> Deserialize<'de> for RequestType {
             6 => RequestType::Unsubscribe,
             7 => RequestType::Publish,
             8 => RequestType::Event,
-            _ => unreachable!("Only numbers 0-8 are valid"),
+            code => RequestType::Unknown(code),
         })
     }
 }
@@ -69,7 +125,32 @@ impl Serialize for RequestType {
     where
         S: Serializer,
     {
-        serializer.serialize_u8(self.clone() as u8)
+        serializer.serialize_u64(self.code())
+    }
+}
+
+impl<'de> Deserialize<'de> for EventType {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let v: Cow<'de, str> = Deserialize::deserialize(deserializer)?;
+
+        Ok(match &*v {
+            "Create" => EventType::Create,
+            "Update" => EventType::Update,
+            "Delete" => EventType::Delete,
+            _ => EventType::Other(v.into_owned()),
+        })
+    }
+}
+
+impl Serialize for EventType {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_str(self.as_str())
     }
 }
 
@@ -78,7 +159,8 @@ impl<'de> Deserialize<'de> for EventKind {
     where
         D: Deserializer<'de>,
     {
-        let v: &str = Deserialize::deserialize(deserializer)?;
+        let v: std::borrow::Cow<'de, str> = Deserialize::deserialize(deserializer)?;
+        let v: &str = &v;
 
         if let Some((event, callback)) = v.split_once('_') {
             match EventKind::from_str(event) {
@@ -86,7 +168,7 @@ impl<'de> Deserialize<'de> for EventKind {
                     Ok(EventKind::JsonApiEventCallback(callback.to_string()))
                 }
                 EventKind::LcdsEvent => Ok(EventKind::LcdsEventCallback(callback.to_string())),
-                _ => unreachable!(),
+                _ => Ok(EventKind::Other(v.to_string())),
             }
         } else {
             Ok(EventKind::from_str(v))
@@ -104,7 +186,7 @@ impl Serialize for EventKind {
 }
 
 impl EventKind {
-    pub(super) fn to_string(&self) -> Cow<'static, str> {
+    pub(crate) fn to_string(&self) -> Cow<'static, str> {
         match self {
             EventKind::JsonApiEvent => "OnJsonApiEvent".into(),
             EventKind::LcdsEvent => "OnLcdsEvent".into(),
@@ -114,18 +196,12 @@ impl EventKind {
             EventKind::ServiceProxyMethodEvent => "OnServiceProxyMethodEvent".into(),
             EventKind::ServiceProxyUuidEvent => "OnServiceProxyUuidEvent".into(),
             EventKind::JsonApiEventCallback(callback) => {
-                let mut callback = callback.replace('/', "_");
-                if &callback[0..1] == "_" {
-                    callback.remove(0);
-                }
-                format!("OnJsonApiEvent_{callback}").into()
+                format!("OnJsonApiEvent_{}", mangle(callback)).into()
             }
             EventKind::LcdsEventCallback(callback) => {
-                let mut callback = callback.replace('/', "_");
-                callback.remove(0);
-
-                format!("OnLcdsEvent_{callback}").into()
+                format!("OnLcdsEvent_{}", mangle(callback)).into()
             }
+            EventKind::Other(event) => event.clone().into(),
         }
     }
 
@@ -138,7 +214,17 @@ impl EventKind {
             "OnServiceProxyAsyncEvent" => EventKind::ServiceProxyAsyncEvent,
             "OnServiceProxyMethodEvent" => EventKind::ServiceProxyMethodEvent,
             "OnServiceProxyUuidEvent" => EventKind::ServiceProxyUuidEvent,
-            event => unreachable!("{}", event),
+            event => EventKind::Other(event.to_string()),
         }
     }
 }
+
+/// Swaps every `/` in the path for `_`, dropping the leading one if there is one
+pub(super) fn mangle(path: &str) -> String {
+    let path = path.replace('/', "_");
+
+    match path.strip_prefix('_') {
+        Some(path) => path.to_string(),
+        None => path,
+    }
+}
@@ -1,4 +1,4 @@
-use crate::ws::types::EventKind;
+use crate::ws::types::{mangle, EventKind};
 use std::collections::HashMap;
 use std::fmt::Debug;
 use std::ops::Index;
@@ -14,6 +14,7 @@ pub struct EventMap<V> {
     service_proxy_uuid_event: V,
     json_api_event_callback: HashMap<String, V>,
     lcds_event_callback: HashMap<String, V>,
+    other: HashMap<String, V>,
 }
 
 impl<V: Default> EventMap<V> {
@@ -28,6 +29,7 @@ impl<V: Default> EventMap<V> {
             service_proxy_uuid_event: V::default(),
             json_api_event_callback: HashMap::new(),
             lcds_event_callback: HashMap::new(),
+            other: HashMap::new(),
         }
     }
 
@@ -42,10 +44,7 @@ impl<V: Default> EventMap<V> {
             EventKind::ServiceProxyMethodEvent => &mut self.service_proxy_method_event,
             EventKind::ServiceProxyUuidEvent => &mut self.service_proxy_uuid_event,
             EventKind::JsonApiEventCallback(key) => {
-                let mut key = key.replace('/', "_");
-                if &key[0..1] == "_" {
-                    key.remove(0);
-                }
+                let key = mangle(key);
 
                 if !self.json_api_event_callback.contains_key(&key) {
                     self.json_api_event_callback
@@ -55,10 +54,7 @@ impl<V: Default> EventMap<V> {
                 self.json_api_event_callback.get_mut(&key).unwrap()
             }
             EventKind::LcdsEventCallback(key) => {
-                let mut key = key.replace('/', "_");
-                if &key[0..1] == "_" {
-                    key.remove(0);
-                }
+                let key = mangle(key);
 
                 if !self.lcds_event_callback.contains_key(&key) {
                     self.lcds_event_callback.insert(key.clone(), V::default());
@@ -66,10 +62,56 @@ impl<V: Default> EventMap<V> {
 
                 self.lcds_event_callback.get_mut(&key).unwrap()
             }
+            EventKind::Other(key) => self.other.entry(key.clone()).or_default(),
         };
 
         events
     }
+
+    /// Iterates over every event kind, along with its value
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = (EventKind, &mut V)> {
+        // The stored keys have already been mangled, adding back the leading
+        // slash means they are mangled the same way when sent
+        let json_api_event_callback = self
+            .json_api_event_callback
+            .iter_mut()
+            .map(|(key, v)| (EventKind::JsonApiEventCallback(format!("/{key}")), v));
+        let lcds_event_callback = self
+            .lcds_event_callback
+            .iter_mut()
+            .map(|(key, v)| (EventKind::LcdsEventCallback(format!("/{key}")), v));
+
+        let other = self
+            .other
+            .iter_mut()
+            .map(|(key, v)| (EventKind::Other(key.clone()), v));
+
+        [
+            (EventKind::JsonApiEvent, &mut self.json_api_event),
+            (EventKind::LcdsEvent, &mut self.lcds_event),
+            (EventKind::Log, &mut self.log),
+            (
+                EventKind::RegionLocaleChanged,
+                &mut self.region_locale_changed,
+            ),
+            (
+                EventKind::ServiceProxyAsyncEvent,
+                &mut self.service_proxy_async_event,
+            ),
+            (
+                EventKind::ServiceProxyMethodEvent,
+                &mut self.service_proxy_method_event,
+            ),
+            (
+                EventKind::ServiceProxyUuidEvent,
+                &mut self.service_proxy_uuid_event,
+            ),
+        ]
+        .into_iter()
+        .chain(json_api_event_callback)
+        .chain(lcds_event_callback)
+        .chain(other)
+    }
 }
 
 impl<V> Index<&EventKind> for EventMap<V> {
@@ -85,21 +127,16 @@ impl<V> Index<&EventKind> for EventMap<V> {
             EventKind::ServiceProxyMethodEvent => &self.service_proxy_method_event,
             EventKind::ServiceProxyUuidEvent => &self.service_proxy_uuid_event,
             EventKind::JsonApiEventCallback(key) => {
-                let mut key = key.replace('/', "_");
-                if &key[0..1] == "_" {
-                    key.remove(0);
-                }
+                let key = mangle(key);
 
                 &self.json_api_event_callback[&key]
             }
             EventKind::LcdsEventCallback(key) => {
-                let mut key = key.replace('/', "_");
-                if &key[0..1] == "_" {
-                    key.remove(0);
-                }
+                let key = mangle(key);
 
                 &self.lcds_event_callback[&key]
             }
+            EventKind::Other(key) => &self.other[key],
         }
     }
 }